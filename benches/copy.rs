@@ -0,0 +1,42 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use timeless::data::{ChunkStorage, ChunkedData};
+
+fn bench_storage<S: ChunkStorage<f32>>(c: &mut Criterion, name: &str, items: &[f32]) {
+    let mut group = c.benchmark_group(name);
+
+    group.bench_function("extend_from_slice", |b| {
+        b.iter(|| {
+            let mut data = ChunkedData::<f32, S>::default();
+            for chunk in black_box(items).chunks(100) {
+                data.extend_from_slice(chunk);
+            }
+            data
+        })
+    });
+
+    group.bench_function("copy_from_slice", |b| {
+        b.iter(|| {
+            let mut data = ChunkedData::<f32, S>::default();
+            for chunk in black_box(items).chunks(100) {
+                data.copy_from_slice(chunk);
+            }
+            data
+        })
+    });
+
+    group.finish();
+}
+
+fn copy(c: &mut Criterion) {
+    let items = (0..100_000).map(|i| i as f32).collect::<Vec<_>>();
+
+    bench_storage::<Vec<f32>>(c, "copy_vec", &items);
+
+    #[cfg(feature = "smallvec")]
+    bench_storage::<smallvec::SmallVec<[f32; 8]>>(c, "copy_smallvec", &items);
+}
+
+criterion_group!(benches, copy);
+criterion_main!(benches);
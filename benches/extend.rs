@@ -0,0 +1,29 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use timeless::data::ChunkedData;
+
+fn extend(c: &mut Criterion) {
+    let items = (0..100_000).collect::<Vec<u64>>();
+
+    c.bench_function("extend_from_slice", |b| {
+        b.iter(|| {
+            let mut data = ChunkedData::<u64>::default();
+            data.extend_from_slice(black_box(&items));
+            data
+        })
+    });
+
+    c.bench_function("push_loop", |b| {
+        b.iter(|| {
+            let mut data = ChunkedData::<u64>::default();
+            for &item in black_box(&items) {
+                data.push(item);
+            }
+            data
+        })
+    });
+}
+
+criterion_group!(benches, extend);
+criterion_main!(benches);
@@ -0,0 +1,27 @@
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use timeless::data::ChunkedData;
+
+/// Constructing an iterator shouldn't scale with the number of chunks.
+fn iter_construction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iter_construction");
+
+    for num_chunks in [10, 100, 1_000, 10_000] {
+        let mut data = ChunkedData::<u64>::default();
+        for i in 0..num_chunks {
+            data.push(i);
+            data.push(i);
+            data.try_push(None);
+        }
+
+        group.bench_with_input(BenchmarkId::from_parameter(num_chunks), &data, |b, data| {
+            b.iter(|| black_box(data).iter_with_index().len())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, iter_construction);
+criterion_main!(benches);
@@ -0,0 +1,42 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use timeless::data::ChunkedData;
+
+fn populate() -> ChunkedData<f64> {
+    let mut data = ChunkedData::default();
+
+    for i in 0..100_000 {
+        if i % 1000 == 999 {
+            data.try_push(None);
+        } else {
+            data.push((i % 977) as f64);
+        }
+    }
+
+    data
+}
+
+fn min_max(c: &mut Criterion) {
+    let data = populate();
+    let range = 10_000..90_000;
+
+    c.bench_function("min_max_in_range", |b| {
+        b.iter(|| black_box(&data).min_max_in_range(black_box(range.clone())))
+    });
+
+    c.bench_function("min_max_iter", |b| {
+        b.iter(|| {
+            black_box(&data)
+                .iter_with_index()
+                .filter(|(index, value)| range.contains(index) && !value.is_nan())
+                .fold(None, |acc, (_, &value)| match acc {
+                    None => Some((value, value)),
+                    Some((min, max)) => Some((f64::min(min, value), f64::max(max, value))),
+                })
+        })
+    });
+}
+
+criterion_group!(benches, min_max);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use timeless::data::ChunkedData;
+
+const CHUNK_SIZE: u64 = 64;
+
+/// Simulate collecting data with regular breaks, pruning old chunks as we go.
+fn prune_push_cycles(data: &mut ChunkedData<u64>, recycle: bool) {
+    for i in 0..1_000 {
+        for j in 0..CHUNK_SIZE {
+            data.push(i * CHUNK_SIZE + j);
+        }
+        data.insert_break();
+
+        if data.length() > 4 * CHUNK_SIZE as usize {
+            let _ = data.prune(CHUNK_SIZE as usize - 1);
+
+            if !recycle {
+                data.clear_recycled_buffers();
+            }
+        }
+    }
+}
+
+fn recycle(c: &mut Criterion) {
+    c.bench_function("prune_push_recycled", |b| {
+        b.iter(|| {
+            let mut data = ChunkedData::default();
+            prune_push_cycles(black_box(&mut data), true);
+            data
+        })
+    });
+
+    c.bench_function("prune_push_not_recycled", |b| {
+        b.iter(|| {
+            let mut data = ChunkedData::default();
+            prune_push_cycles(black_box(&mut data), false);
+            data
+        })
+    });
+}
+
+criterion_group!(benches, recycle);
+criterion_main!(benches);
@@ -1,4 +1,17 @@
 mod chunked;
 pub use chunked::*;
 
-// mod non_chunked;
+mod delta;
+pub use delta::*;
+
+mod non_chunked;
+pub use non_chunked::*;
+
+mod numeric;
+pub use numeric::*;
+
+mod rle;
+pub use rle::*;
+
+mod storage;
+pub use storage::*;
@@ -1,32 +1,60 @@
 //! This is code responsible for possibly chunked data.
 
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::{Add, Index, Range, RangeInclusive},
+    time::Instant,
+};
+
+use super::{ChunkStorage, NonChunkedData, ToF64};
+
+mod cursor;
+pub use cursor::*;
+
+mod view;
+pub use view::*;
+
 #[derive(Clone, Default, Debug)]
-struct DataChunk<T> {
+struct DataChunk<S> {
     /// The start offset of this chunk, should correspond to the time vector
-    /// indices. If that updates, this MUST also update.
+    /// indices. If that updates, this MUST also update (e.g. with [`ChunkedData::rebase`]).
     start_offset: usize,
 
     /// The actual value data!
-    data: Vec<T>,
+    ///
+    /// This stays as `S` even after the chunk is sealed, rather than becoming something
+    /// like a `Box<[T]>`. Sealed chunks can still change (e.g. [`ChunkedData::fill`] and
+    /// [`ChunkedData::remove`]), their buffers are recycled for new chunks, and since every
+    /// chunk lives in the same [`Vec`], a smaller sealed representation wouldn't make each
+    /// chunk any smaller anyway.
+    data: S,
 }
 
-impl<T> DataChunk<T> {
-    fn push(&mut self, item: T) {
+impl<S> DataChunk<S> {
+    fn push<T>(&mut self, item: T)
+    where
+        S: ChunkStorage<T>,
+    {
         self.data.push(item)
     }
 }
 
 /// An iterator created from a [`ChunkedData`].
 pub struct ChunkedDataIter<I: Iterator> {
-    iter: I,
-    size: usize, // TODO: We can probably store this as a `OnceCell` instead to avoid computing it if we don't need it.
+    pub(crate) iter: I,
+    pub(crate) size: usize, // TODO: We can probably store this as a `OnceCell` instead to avoid computing it if we don't need it.
 }
 
 impl<T, I: Iterator<Item = T>> Iterator for ChunkedDataIter<I> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next()
+        let item = self.iter.next()?;
+        self.size -= 1;
+
+        Some(item)
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -38,28 +66,376 @@ impl<T, I: Iterator<Item = T>> ExactSizeIterator for ChunkedDataIter<I> {}
 
 impl<T, I: Iterator<Item = T> + DoubleEndedIterator> DoubleEndedIterator for ChunkedDataIter<I> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.iter.next_back()
+        let item = self.iter.next_back()?;
+        self.size -= 1;
+
+        Some(item)
+    }
+}
+
+/// An iterator of mutable slices over each chunk of a [`ChunkedData`], created from
+/// [`ChunkedData::chunks_mut`].
+pub struct ChunksMutIter<'a, D, S> {
+    chunks: std::slice::IterMut<'a, DataChunk<S>>,
+    _marker: PhantomData<&'a mut D>,
+}
+
+impl<'a, D, S: ChunkStorage<D>> Iterator for ChunksMutIter<'a, D, S> {
+    type Item = &'a mut [D];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next().map(|dc| dc.data.as_mut_slice())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl<D, S: ChunkStorage<D>> ExactSizeIterator for ChunksMutIter<'_, D, S> {}
+
+impl<D, S: ChunkStorage<D>> DoubleEndedIterator for ChunksMutIter<'_, D, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.chunks.next_back().map(|dc| dc.data.as_mut_slice())
+    }
+}
+
+/// What happened when pushing a value that may not have been stored, such as with
+/// [`ChunkedData::try_push_finite`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PushOutcome {
+    /// The value was stored.
+    Pushed,
+
+    /// The value was not stored, and a gap was recorded in its place.
+    Gap,
+}
+
+/// An error returned when trying to prune a [`ChunkedData`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PruneError {
+    /// There are no stored elements to prune.
+    Empty,
+
+    /// The requested index is past the last index.
+    IndexOutOfBounds {
+        /// The index that was requested.
+        requested: usize,
+
+        /// The maximum index that could have been requested.
+        max: usize,
+    },
+}
+
+impl PruneError {
+    /// Check whether pruning up to (and including) `index` is valid, given the
+    /// length and whether there are any stored elements.
+    pub(crate) fn check(index: usize, length: usize, no_elements: bool) -> Result<(), Self> {
+        if length == 0 {
+            Err(Self::Empty)
+        } else if index >= length {
+            Err(Self::IndexOutOfBounds {
+                requested: index,
+                max: length - 1,
+            })
+        } else if no_elements {
+            Err(Self::Empty)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Display for PruneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PruneError::Empty => write!(f, "there are no stored elements to prune"),
+            PruneError::IndexOutOfBounds { requested, max } => {
+                write!(f, "index {requested} is past the maximum index {max}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PruneError {}
+
+/// An error returned when an index is past the length of a [`ChunkedData`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IndexOutOfRangeError {
+    /// The index that was requested.
+    pub index: usize,
+
+    /// The length at the time of the request.
+    pub length: usize,
+}
+
+impl fmt::Display for IndexOutOfRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "index {} out of range 0..{}", self.index, self.length)
+    }
+}
+
+impl std::error::Error for IndexOutOfRangeError {}
+
+/// An error returned when a slice of indices was expected to be sorted, but was not.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnsortedIndicesError {
+    /// The position in the slice of the first index that was smaller than the one before it.
+    pub position: usize,
+}
+
+impl fmt::Display for UnsortedIndicesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "indices are not sorted; the index at position {} is smaller than the one before it",
+            self.position
+        )
+    }
+}
+
+impl std::error::Error for UnsortedIndicesError {}
+
+/// An error returned when a quantile was expected to be within `[0, 1]`, but was not.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidQuantileError {
+    /// The position in the slice of the first quantile that was out of range (or NaN).
+    pub position: usize,
+}
+
+impl fmt::Display for InvalidQuantileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the quantile at position {} is not within [0, 1]",
+            self.position
+        )
+    }
+}
+
+impl std::error::Error for InvalidQuantileError {}
+
+/// An error returned by [`ChunkedData::validate`] when the internal state of a
+/// [`ChunkedData`] is inconsistent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvariantError {
+    /// A chunk does not start after the previous chunk.
+    UnorderedChunk {
+        /// The position of the offending chunk.
+        chunk: usize,
+    },
+
+    /// A chunk starts before the previous chunk ends.
+    OverlappingChunk {
+        /// The position of the offending chunk.
+        chunk: usize,
+    },
+
+    /// A chunk has no elements.
+    EmptyChunk {
+        /// The position of the offending chunk.
+        chunk: usize,
+    },
+
+    /// A chunk ends past the length.
+    ChunkPastLength {
+        /// The position of the offending chunk.
+        chunk: usize,
+    },
+
+    /// The last chunk is marked as active, but there are no chunks.
+    ActiveWithoutChunk,
+
+    /// The last chunk is marked as active, but it doesn't end at the length, so the next
+    /// pushed element would be added at the wrong index.
+    ActiveChunkNotAtEnd,
+}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantError::UnorderedChunk { chunk } => {
+                write!(f, "chunk {chunk} does not start after the previous chunk")
+            }
+            InvariantError::OverlappingChunk { chunk } => {
+                write!(f, "chunk {chunk} overlaps the previous chunk")
+            }
+            InvariantError::EmptyChunk { chunk } => write!(f, "chunk {chunk} is empty"),
+            InvariantError::ChunkPastLength { chunk } => {
+                write!(f, "chunk {chunk} ends past the length")
+            }
+            InvariantError::ActiveWithoutChunk => {
+                write!(f, "there is an active chunk, but no chunks")
+            }
+            InvariantError::ActiveChunkNotAtEnd => {
+                write!(f, "the active chunk does not end at the length")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvariantError {}
+
+/// An error returned when two [`ChunkedData`] were expected to have the same gaps, but did not.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LayoutMismatchError {
+    /// The first logical index where one has a stored element and the other doesn't, or
+    /// the shorter length if they only differ in length.
+    pub index: usize,
+}
+
+impl fmt::Display for LayoutMismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the layouts differ at index {}", self.index)
+    }
+}
+
+impl std::error::Error for LayoutMismatchError {}
+
+/// An error returned by [`ChunkedData::rebase`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RebaseError {
+    /// An element is stored at an index that would have been removed.
+    StoredElement {
+        /// The index of the first stored element.
+        index: usize,
+    },
+
+    /// More entries would have been removed than the length.
+    PastLength {
+        /// How many entries would have been removed.
+        removed: usize,
+
+        /// The length of the [`ChunkedData`].
+        length: usize,
+    },
+}
+
+impl fmt::Display for RebaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RebaseError::StoredElement { index } => {
+                write!(
+                    f,
+                    "an element is stored at index {index}, which would be removed"
+                )
+            }
+            RebaseError::PastLength { removed, length } => {
+                write!(
+                    f,
+                    "cannot remove {removed} entries with a length of {length}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for RebaseError {}
+
+/// A summary of how a [`ChunkedData`] is laid out, returned by [`ChunkedData::stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ChunkedStats {
+    /// The number of chunks.
+    pub num_chunks: usize,
+
+    /// The number of stored elements.
+    pub num_elements: usize,
+
+    /// The number of "skipped" elements.
+    pub num_skipped: usize,
+
+    /// The mean number of elements in a chunk, or 0 if there are no chunks.
+    pub average_chunk_length: f64,
+
+    /// The number of elements in the largest chunk.
+    pub max_chunk_length: usize,
+
+    /// The number of gaps, where a gap is a run of consecutive skipped elements.
+    pub num_gaps: usize,
+
+    /// The mean number of skipped elements in a gap, or 0 if there are no gaps.
+    pub average_gap_length: f64,
+
+    /// The number of skipped elements in the largest gap.
+    pub max_gap_length: usize,
+
+    /// Whether the last chunk is still open, i.e. new elements will be added to it.
+    pub is_active: bool,
+}
+
+impl fmt::Display for ChunkedStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "chunks: {} (avg length {:.2}, max {}), elements: {}, gaps: {} (avg length {:.2}, max {}), skipped: {}, active: {}",
+            self.num_chunks,
+            self.average_chunk_length,
+            self.max_chunk_length,
+            self.num_elements,
+            self.num_gaps,
+            self.average_gap_length,
+            self.max_gap_length,
+            self.num_skipped,
+            self.is_active,
+        )
     }
 }
 
+/// The maximum number of spare chunk buffers a [`ChunkedData`] keeps around for reuse.
+const MAX_RECYCLED_BUFFERS: usize = 8;
+
 /// A struct representing data that may potentially have breaks.
 /// If you expect that you may want to store time values but _not_
 /// data values, use this to avoid storing blanks.
-#[derive(Clone, Default, Debug)]
-pub struct ChunkedData<D> {
+///
+/// The values of each chunk are stored using `S`, which defaults to a [`Vec`].
+/// See [`ChunkStorage`] for other options.
+///
+/// Note [`Default`] is implemented for any `D`, even if `D` doesn't implement [`Default`].
+#[derive(Clone)]
+pub struct ChunkedData<D, S = Vec<D>> {
     next_index: usize,
     is_active: bool,
-    chunks: Vec<DataChunk<D>>,
+    chunks: Vec<DataChunk<S>>,
+
+    /// How many elements are stored across all chunks, so it doesn't need to be
+    /// recomputed each time.
+    stored_count: usize,
+
+    /// Buffers from removed chunks, kept around so new chunks don't need to allocate.
+    recycled: Vec<S>,
+
+    /// How many logical entries have ever been pruned from the front, so a
+    /// [`ChunkedCursor`] can tell how far its position has shifted.
+    num_pruned: usize,
+
+    _marker: PhantomData<D>,
 }
 
-impl<D> ChunkedData<D> {
+// This isn't derived, as that would require `D: Default`.
+impl<D, S> Default for ChunkedData<D, S> {
+    fn default() -> Self {
+        Self {
+            next_index: 0,
+            is_active: false,
+            chunks: Vec::new(),
+            stored_count: 0,
+            recycled: Vec::new(),
+            num_pruned: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<D, S: ChunkStorage<D>> ChunkedData<D, S> {
     /// Returns an iterator of items alongside the associated indices for each item.
     pub fn iter_with_index(&self) -> ChunkedDataIter<impl DoubleEndedIterator<Item = (usize, &D)>> {
-        let size = self.chunks.iter().map(|dc| dc.data.len()).sum();
+        let size = self.num_elements();
         let iter = self.chunks.iter().flat_map(|dc| {
             let start = dc.start_offset;
 
             dc.data
+                .as_slice()
                 .iter()
                 .enumerate()
                 .map(move |(offset, datum)| (start + offset, datum))
@@ -68,10 +444,40 @@ impl<D> ChunkedData<D> {
         ChunkedDataIter { iter, size }
     }
 
+    /// An alias of [`ChunkedData::iter_with_index`].
+    #[inline]
+    pub fn iter_indexed(&self) -> ChunkedDataIter<impl DoubleEndedIterator<Item = (usize, &D)>> {
+        self.iter_with_index()
+    }
+
     /// Returns an iterator of items.
     pub fn iter(&self) -> ChunkedDataIter<impl DoubleEndedIterator<Item = &D>> {
-        let size = self.chunks.iter().map(|dc| dc.data.len()).sum();
-        let iter = self.chunks.iter().flat_map(|dc| dc.data.iter());
+        let size = self.num_elements();
+        let iter = self.chunks.iter().flat_map(|dc| dc.data.as_slice().iter());
+
+        ChunkedDataIter { iter, size }
+    }
+
+    /// Returns an iterator over every logical index, yielding [`None`] for gaps.
+    pub fn iter_with_gaps(&self) -> ChunkedDataIter<impl DoubleEndedIterator<Item = Option<&D>>> {
+        let size = self.next_index;
+        let chunk_end = |dc: &DataChunk<S>| dc.start_offset + dc.data.len();
+        let trailing = self.next_index - self.chunks.last().map(chunk_end).unwrap_or(0);
+
+        let iter = self
+            .chunks
+            .iter()
+            .enumerate()
+            .flat_map(move |(index, dc)| {
+                let prev_end = match index.checked_sub(1) {
+                    Some(prev) => chunk_end(&self.chunks[prev]),
+                    None => 0,
+                };
+
+                std::iter::repeat_n(None, dc.start_offset - prev_end)
+                    .chain(dc.data.as_slice().iter().map(Some))
+            })
+            .chain(std::iter::repeat_n(None, trailing));
 
         ChunkedDataIter { iter, size }
     }
@@ -79,19 +485,64 @@ impl<D> ChunkedData<D> {
     /// Returns an iterator of owned items. This consumes the [`ChunkedData`].
     ///
     /// Note this is currently not just `into_iter` due to how it's implemented, this is subject to change.
-    pub fn into_owned_iter(self) -> ChunkedDataIter<impl DoubleEndedIterator<Item = D>> {
-        let size = self.chunks.iter().map(|dc| dc.data.len()).sum();
+    pub fn into_owned_iter(self) -> ChunkedDataIter<impl DoubleEndedIterator<Item = D>>
+    where
+        S: IntoIterator<Item = D>,
+        S::IntoIter: DoubleEndedIterator,
+    {
+        let size = self.num_elements();
         let iter = self.chunks.into_iter().flat_map(|dc| dc.data.into_iter());
 
         ChunkedDataIter { iter, size }
     }
 
+    /// Returns an iterator of owned items alongside the associated indices for each item.
+    /// This consumes the [`ChunkedData`].
+    pub fn into_iter_with_index(
+        self,
+    ) -> ChunkedDataIter<impl DoubleEndedIterator<Item = (usize, D)>>
+    where
+        S: IntoIterator<Item = D>,
+        S::IntoIter: DoubleEndedIterator + ExactSizeIterator,
+    {
+        let size = self.num_elements();
+        let iter = self.chunks.into_iter().flat_map(|dc| {
+            let start = dc.start_offset;
+
+            dc.data
+                .into_iter()
+                .enumerate()
+                .map(move |(offset, datum)| (start + offset, datum))
+        });
+
+        ChunkedDataIter { iter, size }
+    }
+
+    /// Returns an iterator of mutable slices, one for each contiguous run of elements.
+    ///
+    /// This allows updating the elements in place, but not adding or removing elements.
+    pub fn chunks_mut(&mut self) -> ChunksMutIter<'_, D, S> {
+        ChunksMutIter {
+            chunks: self.chunks.iter_mut(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Call `f` on each stored element in place, from oldest to latest. "Skipped"
+    /// elements are not visited.
+    pub fn apply(&mut self, mut f: impl FnMut(&mut D)) {
+        for chunk in self.chunks_mut() {
+            chunk.iter_mut().for_each(&mut f);
+        }
+    }
+
     /// Given a slice that serves as the "base" yielding items `T`, return an iterator of `(T, D)`, where each `D` from
     /// the [`ChunkedData`] has its index associated with that of `base_slice`.
     ///
     /// This is meant to be used alongside a slice of time values.
     ///
     /// Note this will return the minimum of the number of elements in either the base slice or the [`ChunkedData`].
+    /// Since `base_slice` is borrowed for as long as the iterator lives, it can't change during iteration.
     pub fn iter_along_base<'a, T>(
         &'a self, base_slice: &'a [T],
     ) -> ChunkedDataIter<impl DoubleEndedIterator<Item = (&'a T, &'a D)>> {
@@ -125,6 +576,7 @@ impl<D> ChunkedData<D> {
             let to_take = base_slice.len().saturating_sub(start); // Take at most all the values in the chunk, or up to the number of base element indices.
 
             dc.data
+                .as_slice()
                 .iter()
                 .take(to_take)
                 .enumerate()
@@ -139,9 +591,117 @@ impl<D> ChunkedData<D> {
         ChunkedDataIter { iter, size }
     }
 
+    /// Given a sorted slice that serves as the "base" yielding items `T`, return an iterator of
+    /// `(T, D)` like [`ChunkedData::iter_along_base`], but only for base values within `range`.
+    ///
+    /// The bounds are found with a binary search over `base_slice`, so it must be sorted in
+    /// ascending order. Like [`ChunkedData::iter_along_base`], only indices within both the
+    /// base slice and the [`ChunkedData`] are returned.
+    pub fn values_between<'a, T: PartialOrd>(
+        &'a self, base_slice: &'a [T], range: RangeInclusive<T>,
+    ) -> ChunkedDataIter<impl DoubleEndedIterator<Item = (&'a T, &'a D)>> {
+        let (start, end) = range.into_inner();
+        let lower = base_slice.partition_point(|base| *base < start);
+        let upper = base_slice.partition_point(|base| *base <= end);
+
+        let slices = self.slices_in_range(lower..upper);
+        let size = slices.clone().map(|(_, data)| data.len()).sum();
+        let iter = slices.flat_map(move |(start, data)| {
+            data.iter()
+                .enumerate()
+                .map(move |(offset, datum)| (&base_slice[start + offset], datum))
+        });
+
+        ChunkedDataIter { iter, size }
+    }
+
+    /// Given a slice of times that serves as the "base", like in [`ChunkedData::iter_along_base`],
+    /// return an iterator of the per-second rate of change between each pair of adjacent
+    /// elements, alongside the index of the later element. This can be used to turn a
+    /// cumulative counter into a rate.
+    ///
+    /// Pairs are only taken from within a chunk, so no rate is returned for the first
+    /// element after a break. Pairs where the later time is not after the earlier one are
+    /// also skipped, as are any past the end of `base`. If the data decreases (e.g. a
+    /// counter was reset), the rate will be negative.
+    pub fn rate_along_base<'a>(
+        &'a self, base: &'a [Instant],
+    ) -> impl DoubleEndedIterator<Item = (usize, f64)> + 'a
+    where
+        D: ToF64,
+    {
+        self.chunks.iter().flat_map(move |dc| {
+            let start = dc.start_offset;
+
+            dc.data
+                .as_slice()
+                .windows(2)
+                .enumerate()
+                .filter_map(move |(offset, pair)| {
+                    let index = start + offset + 1;
+                    let elapsed = base
+                        .get(index)?
+                        .checked_duration_since(base[index - 1])
+                        .filter(|elapsed| !elapsed.is_zero())?;
+
+                    let change = pair[1].to_f64() - pair[0].to_f64();
+                    Some((index, change / elapsed.as_secs_f64()))
+                })
+        })
+    }
+
+    /// Returns an iterator of each run of `window` consecutive elements, like
+    /// [`slice::windows`], where a window never spans a break.
+    ///
+    /// Windows start fresh at the beginning of each chunk. If a chunk has fewer than
+    /// `window` elements, it is returned as a single shorter window rather than skipped,
+    /// so every chunk is represented.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is 0.
+    pub fn rolling_window_iter(&self, window: usize) -> impl DoubleEndedIterator<Item = &[D]> {
+        assert!(window > 0, "window must be greater than 0");
+
+        self.chunks.iter().flat_map(move |dc| {
+            let data = dc.data.as_slice();
+            data.windows(std::cmp::min(window, data.len()))
+        })
+    }
+
+    /// Return the stored elements within the logical `range`, as pairs of the index of
+    /// the first element and a slice of the elements, for each chunk within the range.
+    fn slices_in_range(
+        &self, range: Range<usize>,
+    ) -> impl DoubleEndedIterator<Item = (usize, &[D])> + Clone {
+        let Range { start, end } = range;
+
+        let first_chunk = self
+            .chunks
+            .partition_point(|c| c.start_offset + c.data.len() <= start);
+        let last_chunk = self.chunks.partition_point(|c| c.start_offset < end);
+        let chunks = &self.chunks[first_chunk..std::cmp::max(first_chunk, last_chunk)];
+
+        chunks.iter().map(move |dc| {
+            let data = dc.data.as_slice();
+            let from = start.saturating_sub(dc.start_offset);
+            let to = std::cmp::min(end - dc.start_offset, data.len());
+
+            (dc.start_offset + from, &data[from..std::cmp::max(from, to)])
+        })
+    }
+
     /// Return how many elements actually are stored in the [`ChunkedData`].
     pub fn num_elements(&self) -> usize {
-        self.chunks.iter().map(|dc| dc.data.len()).sum()
+        self.stored_count
+    }
+
+    /// Return how many elements are stored within the logical `range`, i.e. excluding
+    /// "skipped" elements. Only the chunks overlapping the range are looked at.
+    pub fn count_in_range(&self, range: Range<usize>) -> usize {
+        self.slices_in_range(range)
+            .map(|(_, data)| data.len())
+            .sum()
     }
 
     /// Return the "length" of the [`ChunkedData`], _including_ skipped
@@ -150,24 +710,177 @@ impl<D> ChunkedData<D> {
         self.next_index
     }
 
+    /// Return the active chunk, starting a new one if there isn't one.
+    fn active_chunk_mut(&mut self) -> &mut DataChunk<S> {
+        if !self.is_active {
+            // Start a new chunk.
+            self.chunks.push(DataChunk {
+                start_offset: self.next_index,
+                data: self.recycled.pop().unwrap_or_default(),
+            });
+            self.is_active = true;
+        }
+
+        self.chunks
+            .last_mut()
+            .expect("chunks must be initialized with at least a value if is_active is set")
+    }
+
     /// Push an element.
     pub fn push(&mut self, item: D) {
+        self.active_chunk_mut().push(item);
+        self.next_index += 1;
+        self.stored_count += 1;
+
+        debug_assert!(self.validate().is_ok());
+    }
+
+    /// Push all elements from a slice as one contiguous run, without any gaps.
+    ///
+    /// This is equivalent to calling [`ChunkedData::push`] for each element, but only
+    /// reserves space once.
+    pub fn extend_from_slice(&mut self, items: &[D])
+    where
+        D: Clone,
+    {
+        if items.is_empty() {
+            return;
+        }
+
+        let data = &mut self.active_chunk_mut().data;
+        data.reserve(items.len());
+        data.extend_from_slice(items);
+
+        self.next_index += items.len();
+        self.stored_count += items.len();
+
+        debug_assert!(self.validate().is_ok());
+    }
+
+    /// Like [`ChunkedData::extend_from_slice`], but for [`Copy`] elements, which lets the
+    /// elements be copied over in bulk rather than cloned one at a time.
+    ///
+    /// For [`Vec`]-backed chunks this is the same as [`ChunkedData::extend_from_slice`],
+    /// as [`Vec::extend_from_slice`] already does this. For other backends, like `SmallVec`,
+    /// this guarantees a bulk copy rather than relying on the compiler to optimize the
+    /// clones into one; in practice, the two are usually about as fast (see the `copy`
+    /// benchmark).
+    pub fn copy_from_slice(&mut self, items: &[D])
+    where
+        D: Copy,
+    {
+        if items.is_empty() {
+            return;
+        }
+
+        let data = &mut self.active_chunk_mut().data;
+        data.reserve(items.len());
+        data.copy_from_slice(items);
+
+        self.next_index += items.len();
+        self.stored_count += items.len();
+
+        debug_assert!(self.validate().is_ok());
+    }
+
+    /// Push all elements from a [`Vec`] as one contiguous run, without any gaps.
+    ///
+    /// If there is no active chunk, the [`Vec`] is used to start a new chunk, which
+    /// avoids copying if the chunks are also stored as a [`Vec`].
+    pub fn extend_from_vec(&mut self, items: Vec<D>) {
+        if items.is_empty() {
+            return;
+        }
+
+        let len = items.len();
+
         if self.is_active {
-            let current_chunk = self
-                .chunks
-                .last_mut()
-                .expect("chunks must be initialized with at least a value if is_active is set");
-            current_chunk.push(item);
+            let data = &mut self.active_chunk_mut().data;
+            data.reserve(len);
+            data.extend_from_iter(items);
         } else {
-            // Start a new chunk.
             self.chunks.push(DataChunk {
                 start_offset: self.next_index,
-                data: vec![item],
+                data: S::from_vec(items),
             });
             self.is_active = true;
         }
 
-        self.next_index += 1;
+        self.next_index += len;
+        self.stored_count += len;
+
+        debug_assert!(self.validate().is_ok());
+    }
+
+    /// Reserve capacity for at least `additional` more elements to be pushed without
+    /// reallocating, like [`Vec::reserve`].
+    ///
+    /// If there is an active chunk, this reserves space in it. Otherwise, the space is
+    /// reserved for the next chunk that is started; note this is dropped alongside any
+    /// other recycled buffers (see [`ChunkedData::clear_recycled_buffers`]).
+    pub fn reserve(&mut self, additional: usize) {
+        self.next_chunk_data().reserve(additional);
+    }
+
+    /// Like [`ChunkedData::reserve`], but reserves space for exactly `additional` more
+    /// elements if possible, like [`Vec::reserve_exact`].
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.next_chunk_data().reserve_exact(additional);
+    }
+
+    /// Return how many elements can be stored across all chunks without reallocating,
+    /// like [`Vec::capacity`].
+    ///
+    /// This doesn't include any spare buffers kept around for new chunks, such as space
+    /// reserved by [`ChunkedData::reserve`] while there is no active chunk.
+    pub fn capacity(&self) -> usize {
+        self.chunks.iter().map(|dc| dc.data.capacity()).sum()
+    }
+
+    /// Return how many chunks can be stored without reallocating.
+    pub fn chunk_capacity(&self) -> usize {
+        self.chunks.capacity()
+    }
+
+    /// Return how many bytes the [`ChunkedData`] has allocated on the heap, for memory
+    /// accounting. This counts the allocated capacity rather than just what is used, and
+    /// includes any recycled buffers kept around for new chunks.
+    ///
+    /// This doesn't include any heap memory owned by the elements themselves.
+    pub fn byte_size(&self) -> usize {
+        let element_capacity: usize = self
+            .chunks
+            .iter()
+            .map(|dc| &dc.data)
+            .chain(&self.recycled)
+            .map(|data| data.heap_capacity())
+            .sum();
+
+        size_of::<DataChunk<S>>() * self.chunks.capacity()
+            + size_of::<S>() * self.recycled.capacity()
+            + size_of::<D>() * element_capacity
+    }
+
+    /// Return the storage that the next pushed element will go into, without starting a
+    /// new chunk. If there is no active chunk, this is a buffer for the next chunk.
+    fn next_chunk_data(&mut self) -> &mut S {
+        if self.is_active {
+            return &mut self
+                .chunks
+                .last_mut()
+                .expect("chunks must be initialized with at least a value if is_active is set")
+                .data;
+        }
+
+        // New chunks take the last recycled buffer, so make sure there is one.
+        self.chunks.reserve(1);
+        if self.recycled.is_empty() {
+            self.recycled.push(S::default());
+        }
+
+        self.recycled
+            .last_mut()
+            .expect("a recycled buffer was just added if there were none")
     }
 
     /// Manually mark that a break is needed in the chunk.
@@ -186,6 +899,8 @@ impl<D> ChunkedData<D> {
             None => {
                 self.insert_break();
                 self.next_index += 1;
+
+                debug_assert!(self.validate().is_ok());
             }
         }
     }
@@ -194,366 +909,4320 @@ impl<D> ChunkedData<D> {
     /// elements. This will result in the effective length becoming
     /// `prev_length - index - 1`.
     ///
-    /// If `index` goes past the number of elements, this function will return
-    /// an error containing the stored index in the [`ChunkedData`].
-    pub fn prune(&mut self, index: usize) -> Result<(), usize> {
-        if self.next_index == 0 || self.next_index - 1 < index || self.chunks.is_empty() {
-            return Err(self.next_index);
-        }
+    /// If there are no stored elements, or `index` goes past the length, this function
+    /// will return a [`PruneError`].
+    pub fn prune(&mut self, index: usize) -> Result<(), PruneError> {
+        PruneError::check(index, self.next_index, self.chunks.is_empty())?;
+        self.prune_unchecked(index);
+        debug_assert!(self.validate().is_ok());
 
-        self.next_index -= index + 1;
-
-        let dc_index = match self.chunks.binary_search_by(|c| c.start_offset.cmp(&index)) {
-            Ok(result) => result,
-            Err(result) => {
-                if result > 0 {
-                    result - 1
-                } else {
-                    // Nothing to prune. We still need to change the offsets though.
-                    for chunk in &mut self.chunks {
-                        chunk.start_offset -= index + 1;
-                    }
+        Ok(())
+    }
 
-                    return Ok(());
-                }
-            }
-        };
+    /// Like [`ChunkedData::prune`], but `index` is clamped to the last index rather
+    /// than returning an error. This does nothing if the [`ChunkedData`] has a length
+    /// of zero.
+    ///
+    /// Returns how many logical entries were actually removed.
+    pub fn prune_at_most(&mut self, index: usize) -> usize {
+        if self.next_index == 0 {
+            return 0;
+        }
 
-        // SAFETY: This index must be valid since it was returned from the binary search.
-        let curr = unsafe { self.chunks.get_unchecked_mut(dc_index) };
-        let to_remove = index - curr.start_offset + 1;
+        let index = index.min(self.next_index - 1);
+        self.prune_unchecked(index);
+        debug_assert!(self.validate().is_ok());
 
-        if to_remove <= curr.data.len() {
-            curr.data.drain(..to_remove);
-            curr.start_offset = 0;
+        index + 1
+    }
 
-            // Remove all previous chunks.
-            self.chunks.drain(0..dc_index);
+    /// Remove every logical entry, including "skipped" elements, resetting the length to zero.
+    /// Unlike [`ChunkedData::prune`], this never fails.
+    ///
+    /// This keeps the capacity for chunks allocated, so it can be reused by later pushes.
+    pub fn prune_all(&mut self) {
+        self.remove_chunks(0..self.chunks.len());
+        self.num_pruned += self.next_index;
+        self.next_index = 0;
+        self.is_active = false;
+    }
 
-            // Update offsets for all following chunks.
+    /// Shift every index down by `removed`, dropping that many "skipped" elements from
+    /// the start. This can be used to stay in sync with a time list that was pruned
+    /// separately, such as with [`crate::time::OffsetTimeList::prune`].
+    ///
+    /// Unlike [`ChunkedData::prune`], this never removes stored elements. If any element is
+    /// stored before `removed`, or `removed` is past the length, a [`RebaseError`] is
+    /// returned and nothing is changed.
+    pub fn rebase(&mut self, removed: usize) -> Result<(), RebaseError> {
+        if removed > self.next_index {
+            return Err(RebaseError::PastLength {
+                removed,
+                length: self.next_index,
+            });
+        }
+
+        if let Some(first) = self.chunks.first()
+            && first.start_offset < removed
+        {
+            return Err(RebaseError::StoredElement {
+                index: first.start_offset,
+            });
+        }
+
+        for chunk in &mut self.chunks {
+            chunk.start_offset -= removed;
+        }
+
+        self.next_index -= removed;
+        self.num_pruned += removed;
+
+        Ok(())
+    }
+
+    /// The actual pruning logic; `index` must be less than the length.
+    fn prune_unchecked(&mut self, index: usize) {
+        self.next_index -= index + 1;
+        self.num_pruned += index + 1;
+
+        // The last chunk starting at or before `index`. Searching for the last one (rather
+        // than any one) means the arithmetic below never lands on the wrong chunk.
+        let Some(dc_index) = self
+            .chunks
+            .partition_point(|c| c.start_offset <= index)
+            .checked_sub(1)
+        else {
+            // Nothing to prune. We still need to change the offsets though.
+            for chunk in &mut self.chunks {
+                chunk.start_offset -= index + 1;
+            }
+
+            return;
+        };
+
+        // SAFETY: This index must be valid since it was returned from the binary search.
+        let curr = unsafe { self.chunks.get_unchecked_mut(dc_index) };
+        let to_remove = index - curr.start_offset + 1;
+
+        if to_remove < curr.data.len() {
+            curr.data.drain_front(to_remove);
+            curr.start_offset = 0;
+            self.stored_count -= to_remove;
+
+            // Remove all previous chunks.
+            self.remove_chunks(0..dc_index);
+
+            // Update offsets for all following chunks. These shift by every logical entry
+            // removed, not just the ones removed from this chunk, as it may have had a gap
+            // before it.
             for chunk in self.chunks.iter_mut().skip(1) {
-                chunk.start_offset -= to_remove;
+                chunk.start_offset -= index + 1;
+            }
+        } else {
+            // Drain this chunk too, rather than leaving it behind empty.
+            self.remove_chunks(0..dc_index + 1);
+
+            if self.chunks.is_empty() {
+                self.is_active = false;
+            }
+
+            for chunk in &mut self.chunks {
+                chunk.start_offset -= index + 1;
+            }
+        }
+    }
+
+    /// Remove the chunks within `range`, recycling their buffers.
+    fn remove_chunks(&mut self, range: Range<usize>) {
+        for dc in self.chunks.drain(range) {
+            self.stored_count -= dc.data.len();
+            Self::recycle(&mut self.recycled, dc.data);
+        }
+    }
+
+    /// Remove any chunks without data, recycling their buffers. If the last chunk
+    /// was removed, this also seals it.
+    fn remove_empty_chunks(&mut self) {
+        if self.chunks.last().is_some_and(|c| c.data.is_empty()) {
+            self.is_active = false;
+        }
+
+        let recycled = &mut self.recycled;
+        self.chunks.retain_mut(|c| {
+            if c.data.is_empty() {
+                Self::recycle(recycled, std::mem::take(&mut c.data));
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Keep `data`'s buffer around for a future chunk, if there's room.
+    fn recycle(recycled: &mut Vec<S>, mut data: S) {
+        if recycled.len() < MAX_RECYCLED_BUFFERS {
+            data.clear();
+            recycled.push(data);
+        }
+    }
+
+    /// Drop any spare buffers kept from removed chunks for reuse by new chunks.
+    pub fn clear_recycled_buffers(&mut self) {
+        self.recycled = Vec::new();
+    }
+
+    /// Replace the element at `index` with `value`, returning the old element.
+    ///
+    /// If `index` is a gap or out of range, nothing is changed and [`None`] is returned.
+    /// To also populate gaps, see [`ChunkedData::fill`].
+    pub fn set(&mut self, index: usize, value: D) -> Option<D> {
+        let (chunk_index, offset) = self.locate(index)?;
+
+        Some(std::mem::replace(
+            &mut self.chunks[chunk_index].data.as_mut_slice()[offset],
+            value,
+        ))
+    }
+
+    /// Write `value` at `index`, returning the old element if there was one. Unlike
+    /// [`ChunkedData::set`], this will also populate `index` if it is a gap, either by
+    /// extending an adjacent chunk or by creating a new chunk.
+    ///
+    /// This does not change the length of the [`ChunkedData`]; if `index` is out of range,
+    /// an error is returned.
+    pub fn fill(&mut self, index: usize, value: D) -> Result<Option<D>, IndexOutOfRangeError> {
+        if index >= self.next_index {
+            return Err(IndexOutOfRangeError {
+                index,
+                length: self.next_index,
+            });
+        }
+
+        let next_chunk = self.chunks.partition_point(|c| c.start_offset <= index);
+        let prev_end = next_chunk
+            .checked_sub(1)
+            .map(|prev| self.chunks[prev].start_offset + self.chunks[prev].data.len());
+
+        match prev_end {
+            Some(prev_end) if prev_end > index => {
+                // It's already stored, so just replace it.
+                let prev = &mut self.chunks[next_chunk - 1];
+                let offset = index - prev.start_offset;
+
+                return Ok(Some(std::mem::replace(
+                    &mut prev.data.as_mut_slice()[offset],
+                    value,
+                )));
+            }
+            _ => {}
+        }
+
+        let joins_prev = prev_end == Some(index);
+        let joins_next = self
+            .chunks
+            .get(next_chunk)
+            .is_some_and(|c| c.start_offset == index + 1);
+
+        match (joins_prev, joins_next) {
+            (true, true) => {
+                // This fills the only gap between two chunks, so merge them.
+                let mut next = self.chunks.remove(next_chunk);
+                let next_len = next.data.len();
+                let prev = &mut self.chunks[next_chunk - 1];
+                prev.data.push(value);
+                prev.data
+                    .extend_from_iter(next.data.drain_range(0..next_len));
+                Self::recycle(&mut self.recycled, next.data);
+            }
+            (true, false) => {
+                self.chunks[next_chunk - 1].data.push(value);
+            }
+            (false, true) => {
+                let next = &mut self.chunks[next_chunk];
+                next.data.insert(0, value);
+                next.start_offset -= 1;
+            }
+            (false, false) => {
+                let mut data = self.recycled.pop().unwrap_or_default();
+                data.push(value);
+
+                self.chunks.insert(
+                    next_chunk,
+                    DataChunk {
+                        start_offset: index,
+                        data,
+                    },
+                );
+            }
+        }
+
+        self.stored_count += 1;
+
+        Ok(None)
+    }
+
+    /// Remove the element at `index`, leaving a gap in its place, and return it.
+    /// This does not change the length of the [`ChunkedData`].
+    ///
+    /// Returns [`None`] if `index` is already a gap or is out of range.
+    pub fn remove(&mut self, index: usize) -> Option<D> {
+        let (chunk_index, offset) = self.locate(index)?;
+        let is_last_chunk = chunk_index + 1 == self.chunks.len();
+        self.stored_count -= 1;
+
+        let dc = &mut self.chunks[chunk_index];
+        let len = dc.data.len();
+
+        if offset + 1 == len {
+            // Removing from the end of a chunk; this also seals the chunk since there's now a gap.
+            let item = dc.data.drain_range(offset..len).pop();
+
+            if dc.data.is_empty() {
+                // This is empty, so it doesn't change the count.
+                self.remove_chunks(chunk_index..chunk_index + 1);
+            }
+
+            if is_last_chunk {
+                self.is_active = false;
             }
+
+            item
+        } else if offset == 0 {
+            // Removing from the start of a chunk.
+            dc.start_offset += 1;
+            dc.data.drain_range(0..1).pop()
         } else {
-            // Drain this chunk too.
-            self.chunks.drain(0..=dc_index);
+            // Removing from the middle of a chunk, so split it in two.
+            let tail = dc.data.drain_range(offset + 1..len);
+            let item = dc.data.drain_range(offset..offset + 1).pop();
+
+            self.chunks.insert(
+                chunk_index + 1,
+                DataChunk {
+                    start_offset: index + 1,
+                    data: S::from_vec(tail),
+                },
+            );
+
+            item
+        }
+    }
+
+    /// Remove all logical entries within `range`, including "skipped" elements,
+    /// returning an iterator of the removed elements alongside their indices.
+    ///
+    /// Entries after the range are shifted down, so the effective length becomes
+    /// `prev_length - range.len()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end, or if the end
+    /// of the range is greater than the length.
+    pub fn drain(
+        &mut self, range: Range<usize>,
+    ) -> ChunkedDataIter<impl DoubleEndedIterator<Item = (usize, D)> + use<D, S>> {
+        let Range { start, end } = range;
+        assert!(
+            start <= end,
+            "range start {start} is greater than range end {end}"
+        );
+        assert!(
+            end <= self.next_index,
+            "range end {end} out of range for length {}",
+            self.next_index
+        );
+
+        let removed = end - start;
+        let mut drained = Vec::new();
+
+        let first_chunk = self
+            .chunks
+            .partition_point(|c| c.start_offset + c.data.len() <= start);
+        let last_chunk = self.chunks.partition_point(|c| c.start_offset < end);
+
+        for dc in &mut self.chunks[first_chunk..std::cmp::max(first_chunk, last_chunk)] {
+            let from = start.saturating_sub(dc.start_offset);
+            let to = std::cmp::min(end - dc.start_offset, dc.data.len());
+            let first_index = dc.start_offset + from;
+
+            drained.extend(
+                dc.data
+                    .drain_range(from..to)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(offset, datum)| (first_index + offset, datum)),
+            );
+
+            // Anything left in a chunk that started within the range now starts where the range did.
+            if dc.start_offset > start {
+                dc.start_offset = start;
+            }
+        }
+
+        for dc in &mut self.chunks[last_chunk..] {
+            dc.start_offset -= removed;
+        }
+
+        self.remove_empty_chunks();
+        self.next_index -= removed;
+        self.stored_count -= drained.len();
+
+        let size = drained.len();
+        ChunkedDataIter {
+            iter: drained.into_iter(),
+            size,
+        }
+    }
+
+    /// Remove the first `n` logical entries, including "skipped" elements, returning an
+    /// iterator of the removed elements. This leaves the [`ChunkedData`] the same as
+    /// pruning up to `n - 1` with [`ChunkedData::prune`] would, including for any
+    /// [`ChunkedCursor`]s.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the length.
+    pub fn drain_front(
+        &mut self, n: usize,
+    ) -> ChunkedDataIter<impl DoubleEndedIterator<Item = D> + use<D, S>> {
+        assert!(
+            n <= self.next_index,
+            "drain count {n} out of range for length {}",
+            self.next_index
+        );
+
+        let drained = self.drain(0..n);
+        self.num_pruned += n;
+
+        ChunkedDataIter {
+            size: drained.len(),
+            iter: drained.map(|(_, datum)| datum),
+        }
+    }
+
+    /// Rotate the logical entries in place such that the first `n` entries move to
+    /// the end, and entry `n` becomes the first. "Skipped" elements are rotated
+    /// like any other entry, matching [`slice::rotate_left`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the length.
+    pub fn rotate_left(&mut self, n: usize) {
+        let len = self.next_index;
+        assert!(n <= len, "rotation {n} out of range for length {len}");
+
+        if n == 0 || n == len {
+            return;
+        }
+
+        // Split the chunk straddling the rotation point, if any.
+        if let Some((chunk_index, offset)) = self.locate(n)
+            && offset > 0
+        {
+            let chunk = &mut self.chunks[chunk_index];
+            let chunk_len = chunk.data.len();
+            let tail = chunk.data.drain_range(offset..chunk_len);
+
+            self.chunks.insert(
+                chunk_index + 1,
+                DataChunk {
+                    start_offset: n,
+                    data: S::from_vec(tail),
+                },
+            );
+        }
+
+        let split = self.chunks.partition_point(|c| c.start_offset < n);
+        for dc in &mut self.chunks[..split] {
+            dc.start_offset += len - n;
+        }
+        for dc in &mut self.chunks[split..] {
+            dc.start_offset -= n;
+        }
+        self.chunks.rotate_left(split);
+
+        // The last chunk may no longer end at the length, so start a new one on
+        // the next push.
+        self.is_active = false;
+    }
+
+    /// Rotate the logical entries in place such that the last `n` entries move to
+    /// the front. "Skipped" elements are rotated like any other entry, matching
+    /// [`slice::rotate_right`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than the length.
+    pub fn rotate_right(&mut self, n: usize) {
+        let len = self.next_index;
+        assert!(n <= len, "rotation {n} out of range for length {len}");
+
+        self.rotate_left(len - n);
+    }
+
+    /// Shrink the [`ChunkedData`] after. This also drops any recycled buffers.
+    pub fn shrink_to_fit(&mut self) {
+        for chunk in &mut self.chunks {
+            chunk.data.shrink_to_fit();
+        }
+
+        self.chunks.shrink_to_fit();
+        self.clear_recycled_buffers();
+    }
+
+    /// Convenience function to prune _and_ shrink the [`ChunkedData`] after.
+    pub fn prune_and_shrink_to_fit(&mut self, index: usize) -> Result<(), PruneError> {
+        self.prune(index)?;
+        debug_assert!(self.validate().is_ok());
+
+        self.shrink_to_fit();
+
+        Ok(())
+    }
+
+    /// Try and return the first element.
+    pub fn first(&self) -> Option<&D> {
+        self.chunks
+            .first()
+            .and_then(|chunk| chunk.data.as_slice().first())
+    }
+
+    /// Try and return the last element.
+    pub fn last(&self) -> Option<&D> {
+        self.chunks
+            .last()
+            .and_then(|chunk| chunk.data.as_slice().last())
+    }
+
+    /// Return the start index and elements of the latest contiguous run (i.e. the last chunk),
+    /// or [`None`] if there are no stored elements.
+    ///
+    /// Note that a run stays the latest even after a break is inserted, until something new
+    /// is pushed.
+    pub fn latest_run(&self) -> Option<(usize, &[D])> {
+        self.chunks
+            .last()
+            .map(|chunk| (chunk.start_offset, chunk.data.as_slice()))
+    }
+
+    /// Returns an iterator of items alongside their indices for the latest contiguous run.
+    /// See [`ChunkedData::latest_run`].
+    pub fn iter_latest_run(&self) -> ChunkedDataIter<impl DoubleEndedIterator<Item = (usize, &D)>> {
+        let (start, data) = self.latest_run().unwrap_or((0, &[]));
+        let iter = data
+            .iter()
+            .enumerate()
+            .map(move |(offset, datum)| (start + offset, datum));
+
+        ChunkedDataIter {
+            iter,
+            size: data.len(),
+        }
+    }
+
+    /// Return the elements of the last chunk, or [`None`] if there are no stored elements.
+    /// See [`ChunkedData::latest_run`].
+    #[inline]
+    pub fn last_chunk_data(&self) -> Option<&[D]> {
+        self.chunks.last().map(|chunk| chunk.data.as_slice())
+    }
+
+    /// Return the index of the first element in the last chunk, or [`None`] if there are
+    /// no stored elements. See [`ChunkedData::latest_run`].
+    #[inline]
+    pub fn last_chunk_start_offset(&self) -> Option<usize> {
+        self.chunks.last().map(|chunk| chunk.start_offset)
+    }
+
+    /// Returns an iterator of each chunk's start index and elements, starting from the
+    /// latest chunk.
+    pub fn iter_chunks_rev(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (usize, &[D])> + ExactSizeIterator {
+        self.chunks
+            .iter()
+            .rev()
+            .map(|chunk| (chunk.start_offset, chunk.data.as_slice()))
+    }
+
+    /// Returns an iterator of at most `limit` items alongside their indices, starting from
+    /// the latest element.
+    ///
+    /// Chunks are only visited as needed, so older chunks aren't touched once `limit`
+    /// elements have been returned.
+    pub fn rev_elements_until(
+        &self, limit: usize,
+    ) -> ChunkedDataIter<impl Iterator<Item = (usize, &D)>> {
+        let size = std::cmp::min(limit, self.num_elements());
+        let iter = self
+            .iter_chunks_rev()
+            .flat_map(|(start, data)| {
+                data.iter()
+                    .enumerate()
+                    .rev()
+                    .map(move |(offset, datum)| (start + offset, datum))
+            })
+            .take(limit);
+
+        ChunkedDataIter { iter, size }
+    }
+
+    /// Return whether there are zero elements left stored internally.
+    pub fn no_elements(&self) -> bool {
+        self.num_elements() == 0
+    }
+
+    /// Return whether there are no gaps, i.e. every index up to the length has a stored
+    /// element. An empty [`ChunkedData`] is also contiguous.
+    pub fn is_contiguous(&self) -> bool {
+        match self.chunks.as_slice() {
+            [] => self.next_index == 0,
+            [chunk] => chunk.start_offset == 0 && chunk.data.len() == self.next_index,
+            _ => false,
+        }
+    }
+
+    /// Return a summary of how the [`ChunkedData`] is laid out, such as the number
+    /// and size of chunks and gaps.
+    pub fn stats(&self) -> ChunkedStats {
+        let mut stats = ChunkedStats {
+            num_chunks: self.chunks.len(),
+            is_active: self.is_active,
+            ..Default::default()
+        };
+
+        let add_gap = |stats: &mut ChunkedStats, gap: usize| {
+            if gap > 0 {
+                stats.num_gaps += 1;
+                stats.num_skipped += gap;
+                stats.max_gap_length = std::cmp::max(stats.max_gap_length, gap);
+            }
+        };
+
+        let mut next_start = 0;
+        for dc in &self.chunks {
+            add_gap(&mut stats, dc.start_offset - next_start);
+
+            let len = dc.data.len();
+            stats.num_elements += len;
+            stats.max_chunk_length = std::cmp::max(stats.max_chunk_length, len);
+            next_start = dc.start_offset + len;
+        }
+        add_gap(&mut stats, self.next_index - next_start);
+
+        if stats.num_chunks > 0 {
+            stats.average_chunk_length = stats.num_elements as f64 / stats.num_chunks as f64;
+        }
+
+        if stats.num_gaps > 0 {
+            stats.average_gap_length = stats.num_skipped as f64 / stats.num_gaps as f64;
+        }
+
+        stats
+    }
+
+    /// Check that the internal state is consistent, returning the first problem found
+    /// as an [`InvariantError`].
+    ///
+    /// This should always succeed; it is mainly meant for debugging and testing.
+    pub fn validate(&self) -> Result<(), InvariantError> {
+        if self.is_active && self.chunks.is_empty() {
+            return Err(InvariantError::ActiveWithoutChunk);
+        }
+
+        let mut prev: Option<&DataChunk<S>> = None;
+
+        for (chunk, dc) in self.chunks.iter().enumerate() {
+            if dc.data.is_empty() {
+                return Err(InvariantError::EmptyChunk { chunk });
+            }
+
+            if let Some(prev) = prev {
+                if dc.start_offset <= prev.start_offset {
+                    return Err(InvariantError::UnorderedChunk { chunk });
+                } else if prev.start_offset + prev.data.len() > dc.start_offset {
+                    return Err(InvariantError::OverlappingChunk { chunk });
+                }
+            }
+
+            if dc.start_offset + dc.data.len() > self.next_index {
+                return Err(InvariantError::ChunkPastLength { chunk });
+            }
+
+            prev = Some(dc);
+        }
+
+        if self.is_active
+            && let Some(last) = self.chunks.last()
+            && last.start_offset + last.data.len() != self.next_index
+        {
+            return Err(InvariantError::ActiveChunkNotAtEnd);
+        }
+
+        debug_assert_eq!(
+            self.stored_count,
+            self.chunks.iter().map(|dc| dc.data.len()).sum::<usize>(),
+            "the cached element count doesn't match the chunks"
+        );
+
+        Ok(())
+    }
+
+    /// Find the chunk containing `index`, returning the position of the chunk
+    /// and the position of the element within that chunk.
+    fn locate(&self, index: usize) -> Option<(usize, usize)> {
+        let chunk_index = self
+            .chunks
+            .partition_point(|c| c.start_offset <= index)
+            .checked_sub(1)?;
+
+        // SAFETY: This index must be valid since it was returned from the binary search.
+        let chunk = unsafe { self.chunks.get_unchecked(chunk_index) };
+        let offset = index - chunk.start_offset;
+
+        (offset < chunk.data.len()).then_some((chunk_index, offset))
+    }
+
+    /// Return the element at `index`, or [`None`] if `index` is a gap or out of range.
+    ///
+    /// If the index is known to be stored, indexing (e.g. `data[index]`) can be used
+    /// instead.
+    pub fn get(&self, index: usize) -> Option<&D> {
+        self.locate(index)
+            .map(|(chunk_index, offset)| &self.chunks[chunk_index].data.as_slice()[offset])
+    }
+
+    /// Return whether there is an element stored at `index`.
+    pub fn contains_index(&self, index: usize) -> bool {
+        self.locate(index).is_some()
+    }
+
+    /// Return the first index at or after `index` that has a stored element, or [`None`]
+    /// if there are no stored elements from `index` onwards.
+    pub fn index_of_first_at_or_after(&self, index: usize) -> Option<usize> {
+        let next_chunk = self.chunks.partition_point(|c| c.start_offset <= index);
+
+        if let Some(prev) = next_chunk.checked_sub(1).map(|prev| &self.chunks[prev])
+            && index < prev.start_offset + prev.data.len()
+        {
+            return Some(index);
+        }
+
+        self.chunks.get(next_chunk).map(|c| c.start_offset)
+    }
+
+    /// Return the last index at or before `index` that has a stored element, or [`None`]
+    /// if there are no stored elements up to `index`.
+    pub fn index_of_last_at_or_before(&self, index: usize) -> Option<usize> {
+        let prev = self
+            .chunks
+            .partition_point(|c| c.start_offset <= index)
+            .checked_sub(1)?;
+        let chunk = &self.chunks[prev];
+
+        Some(std::cmp::min(
+            index,
+            chunk.start_offset + chunk.data.len() - 1,
+        ))
+    }
+
+    /// Return the logical index of the first stored element for which `pred` returns false,
+    /// or [`None`] if it returns true for every stored element, like [`slice::partition_point`].
+    ///
+    /// The stored elements must be partitioned by `pred` (e.g. if the data is sorted),
+    /// otherwise the result is meaningless. Gaps are skipped, but the returned index still
+    /// counts them.
+    pub fn partition_point(&self, mut pred: impl FnMut(&D) -> bool) -> Option<usize> {
+        let chunk = self
+            .chunks
+            .partition_point(|c| c.data.as_slice().last().is_some_and(&mut pred));
+        let chunk = self.chunks.get(chunk)?;
+
+        Some(chunk.start_offset + chunk.data.as_slice().partition_point(pred))
+    }
+
+    /// Binary search the stored elements with a comparator function, like
+    /// [`slice::binary_search_by`].
+    ///
+    /// If a match is found, its logical index is returned in [`Ok`]. Otherwise, [`Err`]
+    /// contains the logical index of the first stored element greater than the target,
+    /// or the length if there isn't one. The stored elements must be sorted, otherwise
+    /// the result is meaningless. Gaps are skipped, but the returned indices still count
+    /// them.
+    pub fn binary_search_by(
+        &self, mut f: impl FnMut(&D) -> std::cmp::Ordering,
+    ) -> Result<usize, usize> {
+        let chunk = self.chunks.partition_point(|c| {
+            c.data
+                .as_slice()
+                .last()
+                .is_some_and(|last| f(last) == std::cmp::Ordering::Less)
+        });
+        let Some(chunk) = self.chunks.get(chunk) else {
+            return Err(self.next_index);
+        };
+
+        chunk
+            .data
+            .as_slice()
+            .binary_search_by(f)
+            .map(|offset| chunk.start_offset + offset)
+            .map_err(|offset| chunk.start_offset + offset)
+    }
+
+    /// Look up the elements at each of the given `indices`, which must be sorted in ascending
+    /// order. The returned values line up with `indices`, with [`None`] for gaps and indices
+    /// that are out of range.
+    ///
+    /// This is cheaper than calling [`ChunkedData::get`] for each index, as each chunk is only
+    /// searched for once. If `indices` is not sorted, an error is returned.
+    pub fn sample_at_indices(
+        &self, indices: &[usize],
+    ) -> Result<Vec<Option<&D>>, UnsortedIndicesError> {
+        if let Some(position) = indices.windows(2).position(|w| w[0] > w[1]) {
+            return Err(UnsortedIndicesError {
+                position: position + 1,
+            });
+        }
+
+        let mut samples = Vec::with_capacity(indices.len());
+        let mut chunks = self.chunks.as_slice();
+
+        for &index in indices {
+            // Skip over any chunks that end before this index.
+            let skip = chunks.partition_point(|c| c.start_offset + c.data.len() <= index);
+            chunks = &chunks[skip..];
+
+            let sample = chunks.first().and_then(|c| {
+                index
+                    .checked_sub(c.start_offset)
+                    .and_then(|offset| c.data.as_slice().get(offset))
+            });
+            samples.push(sample);
+        }
+
+        Ok(samples)
+    }
+
+    /// Return the element with the maximum key alongside its logical index, or [`None`]
+    /// if there are no stored elements. If several elements are equally maximum, the last
+    /// one is returned, like [`Iterator::max_by_key`].
+    pub fn max_by_key<K: Ord>(&self, mut f: impl FnMut(&D) -> K) -> Option<(usize, &D)> {
+        self.iter_with_index().max_by_key(|(_, datum)| f(datum))
+    }
+
+    /// Return the element with the minimum key alongside its logical index, or [`None`]
+    /// if there are no stored elements. If several elements are equally minimum, the first
+    /// one is returned, like [`Iterator::min_by_key`].
+    pub fn min_by_key<K: Ord>(&self, mut f: impl FnMut(&D) -> K) -> Option<(usize, &D)> {
+        self.iter_with_index().min_by_key(|(_, datum)| f(datum))
+    }
+
+    /// Return the `q`-th quantile of the stored elements, where `q` is within `[0, 1]`, or
+    /// [`None`] if there are no stored elements. See [`ChunkedData::quantiles_in_range`].
+    pub fn quantile(&self, q: f64) -> Result<Option<D>, InvalidQuantileError>
+    where
+        D: Ord + Clone,
+    {
+        self.quantiles_in_range(0..self.next_index, &[q])
+            .map(|quantiles| quantiles.into_iter().next())
+    }
+
+    /// Return each of the quantiles in `qs` of the stored elements, or an empty [`Vec`] if
+    /// there are no stored elements. See [`ChunkedData::quantiles_in_range`].
+    pub fn quantiles(&self, qs: &[f64]) -> Result<Vec<D>, InvalidQuantileError>
+    where
+        D: Ord + Clone,
+    {
+        self.quantiles_in_range(0..self.next_index, qs)
+    }
+
+    /// Return each of the quantiles in `qs` of the elements within the logical `range`,
+    /// lining up with `qs`. If there are no elements within the range, an empty [`Vec`] is
+    /// returned.
+    ///
+    /// Each quantile must be within `[0, 1]`, where 0 is the minimum and 1 is the maximum.
+    /// Otherwise, an error is returned. Values aren't interpolated; the `q`-th quantile of
+    /// `n` elements is the element that would be at index `round(q * (n - 1))` if they were
+    /// sorted.
+    ///
+    /// The elements are copied into a scratch buffer and found with a selection algorithm,
+    /// rather than being fully sorted.
+    pub fn quantiles_in_range(
+        &self, range: Range<usize>, qs: &[f64],
+    ) -> Result<Vec<D>, InvalidQuantileError>
+    where
+        D: Ord + Clone,
+    {
+        if let Some(position) = qs.iter().position(|q| !(0.0..=1.0).contains(q)) {
+            return Err(InvalidQuantileError { position });
+        }
+
+        let mut scratch = Vec::new();
+        for (_, data) in self.slices_in_range(range) {
+            scratch.extend_from_slice(data);
+        }
+
+        if scratch.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let last = scratch.len() - 1;
+        let mut ranks = qs
+            .iter()
+            .enumerate()
+            .map(|(position, q)| (position, (q * last as f64).round() as usize))
+            .collect::<Vec<_>>();
+        ranks.sort_unstable_by_key(|&(_, rank)| rank);
+
+        // After selecting a rank, everything after it is at least as large, so the next
+        // (larger) rank only needs to be searched for after it.
+        let mut quantiles = vec![None; qs.len()];
+        let mut searched = 0;
+        for (position, rank) in ranks {
+            let (_, quantile, _) = scratch[searched..].select_nth_unstable(rank - searched);
+            quantiles[position] = Some(quantile.clone());
+            searched = rank;
+        }
+
+        Ok(quantiles.into_iter().flatten().collect())
+    }
+
+    /// Return the elements in the logical range `start..end` as a slice, or [`None`] if
+    /// any index in the range is a gap. If the range spans multiple adjacent chunks,
+    /// the elements are copied into `scratch`.
+    fn contiguous_run<'a>(
+        &'a self, start: usize, end: usize, scratch: &'a mut Vec<D>,
+    ) -> Option<&'a [D]>
+    where
+        D: Clone,
+    {
+        let len = end - start;
+        let (mut chunk_index, offset) = self.locate(start)?;
+        let chunk = &self.chunks[chunk_index];
+        let data = &chunk.data.as_slice()[offset..];
+
+        if data.len() >= len {
+            return Some(&data[..len]);
+        }
+
+        // The run continues into the next chunk(s), as long as there are no gaps between them.
+        scratch.clear();
+        scratch.extend_from_slice(data);
+        let mut next_start = chunk.start_offset + chunk.data.len();
+
+        while scratch.len() < len {
+            chunk_index += 1;
+            let chunk = self.chunks.get(chunk_index)?;
+            if chunk.start_offset != next_start {
+                return None;
+            }
+
+            let data = chunk.data.as_slice();
+            let to_take = std::cmp::min(len - scratch.len(), data.len());
+            scratch.extend_from_slice(&data[..to_take]);
+            next_start += data.len();
+        }
+
+        Some(scratch)
+    }
+
+    /// Return a new [`ChunkedData`] containing every `factor`-th element, for downsampling.
+    ///
+    /// Each group of `factor` logical indices becomes a single index in the returned
+    /// [`ChunkedData`]. If any index in a group is a gap, the group becomes a gap. The
+    /// returned [`ChunkedData`] has a length of `ceil(self.length() / factor)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is 0.
+    pub fn reduce_by_factor(&self, factor: usize) -> Self
+    where
+        D: Clone,
+    {
+        self.reduce_by_factor_with(factor, |group| group[0].clone())
+    }
+
+    /// Like [`ChunkedData::reduce_by_factor`], but combines each group of `factor` elements
+    /// with `f` (e.g. taking the maximum of each group) rather than just taking the first.
+    ///
+    /// Note that the last group may be smaller than `factor`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is 0.
+    pub fn reduce_by_factor_with<F: Fn(&[D]) -> D>(&self, factor: usize, f: F) -> Self
+    where
+        D: Clone,
+    {
+        assert!(factor > 0, "factor must be greater than 0");
+
+        let mut reduced = Self::default();
+        let mut scratch = Vec::new();
+        let mut start = 0;
+
+        while start < self.next_index {
+            let end = std::cmp::min(start + factor, self.next_index);
+            reduced.try_push(self.contiguous_run(start, end, &mut scratch).map(&f));
+            start = end;
+        }
+
+        reduced
+    }
+
+    /// Return a new [`ChunkedData`] where each element is the average of the last `window`
+    /// elements up to and including it, with the same gaps as this one.
+    ///
+    /// The window never extends across a break; it starts fresh at the beginning of each
+    /// chunk. Elements within the first `window - 1` of a chunk are the average of just the
+    /// elements available so far, rather than being left out.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is 0.
+    pub fn moving_average(&self, window: usize) -> ChunkedData<f64>
+    where
+        D: ToF64,
+    {
+        assert!(window > 0, "window must be greater than 0");
+
+        self.map_chunks(|data| {
+            let mut sum = 0.0;
+
+            (0..data.len())
+                .map(|i| {
+                    sum += data[i].to_f64();
+                    if let Some(leaving) = i.checked_sub(window) {
+                        sum -= data[leaving].to_f64();
+                    }
+
+                    sum / std::cmp::min(i + 1, window) as f64
+                })
+                .collect()
+        })
+    }
+
+    /// Return a new [`ChunkedData`] where each element is the running total of its chunk up
+    /// to and including it, with the same gaps as this one. The total restarts after each
+    /// break; see [`ChunkedData::global_prefix_sum`] to carry it across breaks instead.
+    pub fn prefix_sum(&self) -> ChunkedData<D>
+    where
+        D: Clone + Add<Output = D> + Default,
+    {
+        self.map_chunks(|data| {
+            let mut total = D::default();
+            data.iter()
+                .map(|datum| {
+                    total = total.clone() + datum.clone();
+                    total.clone()
+                })
+                .collect()
+        })
+    }
+
+    /// Like [`ChunkedData::prefix_sum`], but the running total is carried across breaks,
+    /// so each element is the total of every element up to and including it.
+    pub fn global_prefix_sum(&self) -> ChunkedData<D>
+    where
+        D: Clone + Add<Output = D> + Default,
+    {
+        let mut total = D::default();
+        self.map_chunks(|data| {
+            data.iter()
+                .map(|datum| {
+                    total = total.clone() + datum.clone();
+                    total.clone()
+                })
+                .collect()
+        })
+    }
+
+    /// Return a new [`ChunkedData`] with the same layout as this one, where each chunk's
+    /// elements are replaced by the result of `f`. `f` must return as many elements as
+    /// it was given.
+    fn map_chunks<T>(&self, mut f: impl FnMut(&[D]) -> Vec<T>) -> ChunkedData<T> {
+        let chunks = self
+            .chunks
+            .iter()
+            .map(|dc| {
+                let data = f(dc.data.as_slice());
+                debug_assert_eq!(data.len(), dc.data.len());
+
+                DataChunk {
+                    start_offset: dc.start_offset,
+                    data,
+                }
+            })
+            .collect();
+
+        ChunkedData {
+            next_index: self.next_index,
+            is_active: self.is_active,
+            chunks,
+            stored_count: self.stored_count,
+            ..ChunkedData::default()
+        }
+    }
+}
+
+impl<D> ChunkedData<D> {
+    /// Convert this into a [`NonChunkedData`] if it is contiguous (see
+    /// [`ChunkedData::is_contiguous`]), without copying. Otherwise, this is returned as-is.
+    pub fn try_into_non_chunked(mut self) -> Result<NonChunkedData<D>, Self> {
+        if !self.is_contiguous() {
+            return Err(self);
+        }
+
+        Ok(self
+            .chunks
+            .pop()
+            .map(|chunk| chunk.data)
+            .unwrap_or_default()
+            .into())
+    }
+
+    /// Build a [`ChunkedData`] from its parts, like those returned by
+    /// [`ChunkedData::into_parts`]: the length, whether the last chunk is still active
+    /// (i.e. new elements are added to it), and a list of chunks, each being the index
+    /// of its first element and its elements.
+    ///
+    /// The parts are checked like [`ChunkedData::validate`]; the chunks must be in order,
+    /// non-empty, not overlap, and not go past `next_index`, and an active last chunk must
+    /// end at `next_index`. Otherwise, an [`InvariantError`] is returned.
+    pub fn from_parts(
+        next_index: usize, is_active: bool, chunks: Vec<(usize, Vec<D>)>,
+    ) -> Result<Self, InvariantError> {
+        let stored_count = chunks.iter().map(|(_, data)| data.len()).sum();
+
+        let data = Self {
+            next_index,
+            is_active,
+            chunks: chunks
+                .into_iter()
+                .map(|(start_offset, data)| DataChunk { start_offset, data })
+                .collect(),
+            stored_count,
+            ..Self::default()
+        };
+        data.validate()?;
+
+        Ok(data)
+    }
+
+    /// Split this into its length, whether the last chunk is still active, and a list of
+    /// chunks, each being the index of its first element and its elements. This is the
+    /// inverse of [`ChunkedData::from_parts`].
+    pub fn into_parts(self) -> (usize, bool, Vec<(usize, Vec<D>)>) {
+        let chunks = self
+            .chunks
+            .into_iter()
+            .map(|dc| (dc.start_offset, dc.data))
+            .collect();
+
+        (self.next_index, self.is_active, chunks)
+    }
+}
+
+impl<A, B> ChunkedData<(A, B)> {
+    /// Split a [`ChunkedData`] of pairs into two [`ChunkedData`], with the same chunks
+    /// and gaps as the original.
+    pub fn unzip(self) -> (ChunkedData<A>, ChunkedData<B>) {
+        let mut left = ChunkedData::default();
+        let mut right = ChunkedData::default();
+
+        for dc in self.chunks {
+            let (left_data, right_data) = dc.data.into_iter().unzip();
+
+            left.chunks.push(DataChunk {
+                start_offset: dc.start_offset,
+                data: left_data,
+            });
+            right.chunks.push(DataChunk {
+                start_offset: dc.start_offset,
+                data: right_data,
+            });
+        }
+
+        left.next_index = self.next_index;
+        right.next_index = self.next_index;
+        left.is_active = self.is_active;
+        right.is_active = self.is_active;
+        left.stored_count = self.stored_count;
+        right.stored_count = self.stored_count;
+
+        (left, right)
+    }
+}
+
+impl<D> ChunkedData<D> {
+    /// Combine this with `other` into a [`ChunkedData`] of pairs. This is the inverse of
+    /// [`ChunkedData::unzip`].
+    ///
+    /// Both must have the same length and gaps at the same indices, though they may be
+    /// chunked differently. Otherwise, a [`LayoutMismatchError`] is returned.
+    pub fn zip_into<B>(
+        self, other: ChunkedData<B>,
+    ) -> Result<ChunkedData<(D, B)>, LayoutMismatchError> {
+        if let Some(index) = self.layout_mismatch(&other) {
+            return Err(LayoutMismatchError { index });
+        }
+
+        let length = self.next_index;
+        let mut zipped = ChunkedData::default();
+
+        for ((index, left), (_, right)) in self
+            .into_iter_with_index()
+            .zip(other.into_iter_with_index())
+        {
+            if index != zipped.next_index {
+                zipped.insert_break();
+                zipped.next_index = index;
+            }
+
+            zipped.push((left, right));
+        }
+
+        if length != zipped.next_index {
+            zipped.insert_break();
+            zipped.next_index = length;
+        }
+
+        Ok(zipped)
+    }
+
+    /// Return the first logical index where either this or `other` has a stored element
+    /// and the other doesn't, or the shorter length if they only differ in length.
+    fn layout_mismatch<B>(&self, other: &ChunkedData<B>) -> Option<usize> {
+        self.iter_with_gaps()
+            .zip(other.iter_with_gaps())
+            .position(|(left, right)| left.is_some() != right.is_some())
+            .or_else(|| {
+                (self.next_index != other.next_index)
+                    .then(|| std::cmp::min(self.next_index, other.next_index))
+            })
+    }
+}
+
+impl<D> From<NonChunkedData<D>> for ChunkedData<D> {
+    fn from(data: NonChunkedData<D>) -> Self {
+        Self::from(data.into_vec())
+    }
+}
+
+/// Creates a [`ChunkedData`] with all the items in a single chunk, without any gaps.
+///
+/// If the chunks are also stored as a [`Vec`], the [`Vec`] is used as-is without copying.
+impl<D, S: ChunkStorage<D>> From<Vec<D>> for ChunkedData<D, S> {
+    fn from(items: Vec<D>) -> Self {
+        let mut data = Self::default();
+        data.extend_from_vec(items);
+
+        data
+    }
+}
+
+/// Creates a [`ChunkedData`] with all the items in a single chunk, without any gaps.
+impl<D: Clone, S: ChunkStorage<D>> From<&[D]> for ChunkedData<D, S> {
+    fn from(items: &[D]) -> Self {
+        let mut data = Self::default();
+        data.extend_from_slice(items);
+
+        data
+    }
+}
+
+/// Pushes all items as one contiguous run, without any gaps, like [`ChunkedData::push`].
+///
+/// Space is reserved up front based on the iterator's lower size hint.
+impl<D, S: ChunkStorage<D>> Extend<D> for ChunkedData<D, S> {
+    fn extend<I: IntoIterator<Item = D>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter().peekable();
+
+        // Don't start a new chunk if there's nothing to add.
+        if iter.peek().is_none() {
+            return;
+        }
+
+        let data = &mut self.active_chunk_mut().data;
+        let prev_len = data.len();
+        data.reserve(iter.size_hint().0);
+        data.extend_from_iter(iter);
+        let added = data.len() - prev_len;
+
+        self.next_index += added;
+        self.stored_count += added;
+    }
+}
+
+/// Pushes each item like [`ChunkedData::try_push`], so [`None`] is a "skipped" element.
+impl<D, S: ChunkStorage<D>> Extend<Option<D>> for ChunkedData<D, S> {
+    fn extend<I: IntoIterator<Item = Option<D>>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+
+        let (lower, _) = iter.size_hint();
+        if lower > 0 {
+            self.reserve(lower);
+        }
+
+        for item in iter {
+            self.try_push(item);
+        }
+    }
+}
+
+/// Writes bytes as one contiguous run, without any gaps, like [`ChunkedData::extend_from_slice`].
+/// This never fails, and flushing does nothing.
+impl<S: ChunkStorage<u8>> std::io::Write for ChunkedData<u8, S> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Two [`ChunkedData`] are equal if they have the same length, and the same elements
+/// and gaps at the same logical indices, regardless of how they are stored internally.
+impl<D, S, T> PartialEq<ChunkedData<D, T>> for ChunkedData<D, S>
+where
+    D: PartialEq,
+    S: ChunkStorage<D>,
+    T: ChunkStorage<D>,
+{
+    fn eq(&self, other: &ChunkedData<D, T>) -> bool {
+        self.length() == other.length() && self.iter_with_index().eq(other.iter_with_index())
+    }
+}
+
+impl<D: Eq, S: ChunkStorage<D>> Eq for ChunkedData<D, S> {}
+
+/// Like [`PartialEq`], this hashes the logical contents, so two equal [`ChunkedData`]
+/// will have the same hash regardless of how they are stored internally.
+impl<D: Hash, S: ChunkStorage<D>> Hash for ChunkedData<D, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.length().hash(state);
+
+        for item in self.iter_with_gaps() {
+            item.hash(state);
+        }
+    }
+}
+
+/// Implements the float-specific helpers for [`ChunkedData`].
+macro_rules! impl_float_helpers {
+    ($($t:ty),*) => {
+        $(
+            impl<S: ChunkStorage<$t>> ChunkedData<$t, S> {
+                /// Push `value` if it is finite. If it is NaN or infinite, a gap is recorded
+                /// instead, like calling [`ChunkedData::try_push`] with [`None`].
+                pub fn try_push_finite(&mut self, value: $t) -> PushOutcome {
+                    if value.is_finite() {
+                        self.push(value);
+                        PushOutcome::Pushed
+                    } else {
+                        self.try_push(None);
+                        PushOutcome::Gap
+                    }
+                }
+
+                /// Return the minimum and maximum of the elements within the logical `range`,
+                /// or [`None`] if there are none. NaN values are ignored.
+                ///
+                /// This works directly over the stored slices in fixed-size lanes so that the
+                /// compiler can vectorize it, which is much faster than going through an iterator.
+                pub fn min_max_in_range(&self, range: Range<usize>) -> Option<(f64, f64)> {
+                    const LANES: usize = 8;
+
+                    let mut mins = [<$t>::INFINITY; LANES];
+                    let mut maxes = [<$t>::NEG_INFINITY; LANES];
+
+                    for (_, data) in self.slices_in_range(range) {
+                        let lanes = data.chunks_exact(LANES);
+                        let remainder = lanes.remainder();
+
+                        for lane in lanes {
+                            for i in 0..LANES {
+                                // Note `min` and `max` ignore NaN values.
+                                mins[i] = mins[i].min(lane[i]);
+                                maxes[i] = maxes[i].max(lane[i]);
+                            }
+                        }
+
+                        for (i, &value) in remainder.iter().enumerate() {
+                            mins[i] = mins[i].min(value);
+                            maxes[i] = maxes[i].max(value);
+                        }
+                    }
+
+                    let min = mins.into_iter().fold(<$t>::INFINITY, <$t>::min);
+                    let max = maxes.into_iter().fold(<$t>::NEG_INFINITY, <$t>::max);
+
+                    // If everything was NaN (or there was nothing), the min will still be
+                    // greater than the max.
+                    (min <= max).then_some((min.into(), max.into()))
+                }
+            }
+        )*
+    };
+}
+
+impl_float_helpers!(f32, f64);
+
+/// Formats the [`ChunkedData`] on a single line, with each chunk written as a bracketed
+/// run of elements, and `...` wherever there is a gap. This is followed by the total
+/// length and the number of stored elements. For example:
+///
+/// ```
+/// use timeless::data::ChunkedData;
+///
+/// let mut data = ChunkedData::<u32>::default();
+/// data.push(1);
+/// data.push(2);
+/// data.try_push(None);
+/// data.push(4);
+///
+/// assert_eq!(data.to_string(), "[1, 2] ... [4] (length: 4, elements: 3)");
+/// ```
+impl<D: fmt::Display, S: ChunkStorage<D>> fmt::Display for ChunkedData<D, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut next_start = 0;
+
+        for dc in &self.chunks {
+            if dc.start_offset > next_start {
+                f.write_str("... ")?;
+            }
+
+            f.write_str("[")?;
+            for (index, datum) in dc.data.as_slice().iter().enumerate() {
+                if index > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{datum}")?;
+            }
+            f.write_str("] ")?;
+
+            next_start = dc.start_offset + dc.data.len();
+        }
+
+        if self.next_index > next_start {
+            f.write_str("... ")?;
+        }
+
+        write!(
+            f,
+            "(length: {}, elements: {})",
+            self.length(),
+            self.num_elements()
+        )
+    }
+}
+
+/// By default, this is a terse summary of the length, number of chunks, and number of stored
+/// elements. The alternate format (`{:#?}`) also shows how many elements were skipped, and
+/// a line for each chunk with its logical range and first and last elements. For example:
+///
+/// ```
+/// use timeless::data::ChunkedData;
+///
+/// let mut data = ChunkedData::<u32>::default();
+/// data.push(1);
+/// data.push(2);
+/// data.try_push(None);
+/// data.push(4);
+///
+/// assert_eq!(format!("{data:?}"), "ChunkedData { length: 4, chunks: 2, stored: 3 }");
+/// assert_eq!(
+///     format!("{data:#?}"),
+///     "\
+/// ChunkedData {
+///     length: 4,
+///     stored: 3,
+///     skipped: 1,
+///     chunks: [
+///         [0..=1] 2 values (first=1, last=2),
+///         [3..=3] 1 value (first=4, last=4),
+///     ],
+/// }"
+/// );
+/// ```
+impl<D: fmt::Debug, S: ChunkStorage<D>> fmt::Debug for ChunkedData<D, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            /// Summarizes a chunk on a single line.
+            struct ChunkSummary<'a, D> {
+                start: usize,
+                data: &'a [D],
+            }
+
+            impl<D: fmt::Debug> fmt::Debug for ChunkSummary<'_, D> {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    let len = self.data.len();
+                    let (Some(first), Some(last)) = (self.data.first(), self.data.last()) else {
+                        return write!(f, "[{}..{}] 0 values", self.start, self.start);
+                    };
+
+                    write!(
+                        f,
+                        "[{}..={}] {len} {} (first={first:?}, last={last:?})",
+                        self.start,
+                        self.start + len - 1,
+                        if len == 1 { "value" } else { "values" },
+                    )
+                }
+            }
+
+            let chunks = self
+                .chunks
+                .iter()
+                .map(|dc| ChunkSummary {
+                    start: dc.start_offset,
+                    data: dc.data.as_slice(),
+                })
+                .collect::<Vec<_>>();
+
+            f.debug_struct("ChunkedData")
+                .field("length", &self.next_index)
+                .field("stored", &self.stored_count)
+                .field("skipped", &(self.next_index - self.stored_count))
+                .field("chunks", &chunks)
+                .finish()
+        } else {
+            f.debug_struct("ChunkedData")
+                .field("length", &self.next_index)
+                .field("chunks", &self.chunks.len())
+                .field("stored", &self.stored_count)
+                .finish()
+        }
+    }
+}
+
+impl<D, S: ChunkStorage<D>> Index<usize> for ChunkedData<D, S> {
+    type Output = D;
+
+    /// Return the element at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of range, or if `index` is a gap.
+    fn index(&self, index: usize) -> &Self::Output {
+        if index >= self.next_index {
+            panic!("index {index} out of range 0..{}", self.next_index);
+        }
+
+        match self.get(index) {
+            Some(item) => item,
+            None => panic!("index {index} is a gap"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl<D> ChunkedData<D> {
+        /// Build a [`ChunkedData`] directly from its parts, which may be invalid.
+        fn from_raw_parts(
+            next_index: usize, is_active: bool, chunks: Vec<(usize, Vec<D>)>,
+        ) -> Self {
+            let stored_count = chunks.iter().map(|(_, data)| data.len()).sum();
+
+            Self {
+                next_index,
+                is_active,
+                chunks: chunks
+                    .into_iter()
+                    .map(|(start_offset, data)| DataChunk { start_offset, data })
+                    .collect(),
+                stored_count,
+                recycled: Vec::new(),
+                num_pruned: 0,
+                _marker: PhantomData,
+            }
+        }
+    }
+
+    pub(super) const POPULATION: [Option<u64>; 10] = [
+        Some(1),
+        Some(2),
+        Some(3),
+        None,
+        None,
+        None,
+        Some(7),
+        Some(8),
+        Some(9),
+        Some(10),
+    ];
+
+    pub(super) fn test_populate<S: ChunkStorage<u64>>(data: &mut ChunkedData<u64, S>) {
+        for p in POPULATION {
+            data.try_push(p);
+        }
+    }
+
+    /// Generates the common test suite for a given [`ChunkStorage`] backend.
+    macro_rules! storage_tests {
+        ($name:ident, $storage:ty) => {
+            mod $name {
+                use super::*;
+
+                type Data = ChunkedData<u64, $storage>;
+
+                #[test]
+                fn chunked_push() {
+                    let mut data = Data::default();
+                    assert!(data.no_elements());
+
+                    data.try_push(Some(1));
+
+                    assert!(!data.chunks.is_empty());
+                    assert!(data.is_active);
+                    assert_eq!(data.chunks.last().unwrap().data.as_slice(), &[1]);
+                    assert_eq!(data.next_index, 1);
+
+                    data.try_push(Some(2));
+                    data.try_push(None);
+
+                    assert!(!data.is_active);
+                    assert_eq!(data.chunks.len(), 1);
+                    assert_eq!(data.chunks.first().unwrap().data.as_slice(), &[1, 2]);
+                    assert_eq!(data.next_index, 3);
+
+                    data.try_push(None);
+                    assert!(!data.is_active);
+                    assert_eq!(data.next_index, 4);
+
+                    data.try_push(Some(3));
+                    assert!(data.is_active);
+                    assert_eq!(data.chunks.last().unwrap().data.as_slice(), &[3]);
+                    assert_eq!(data.next_index, 5);
+
+                    assert_eq!(data.length(), 5);
+                    assert_eq!(data.num_elements(), 3);
+                }
+
+                /// Ensure that if we push nothing at first, we don't incorrectly try and
+                /// seal nothing.
+                #[test]
+                fn chunked_empty_initial_push() {
+                    let mut data = Data::default();
+
+                    data.try_push(None);
+                    assert!(!data.is_active);
+
+                    data.try_push(Some(1));
+                    assert!(data.is_active);
+                    assert_eq!(data.next_index, 2);
+                }
+
+                /// Initialize data, prune, and insert.
+                #[track_caller]
+                fn test_pruning(to_prune_index: usize, prune: impl Fn(&mut Data, usize)) {
+                    let mut data = Data::default();
+                    test_populate(&mut data);
+
+                    prune(&mut data, to_prune_index);
+
+                    let removed = to_prune_index + 1;
+                    let result = data
+                        .iter_with_index()
+                        .map(|(a, b)| (a, *b))
+                        .collect::<Vec<_>>();
+
+                    let expected = POPULATION
+                        .into_iter()
+                        .skip(removed)
+                        .enumerate()
+                        .filter_map(|(a, b)| b.map(|b| (a, b)))
+                        .collect::<Vec<_>>();
+
+                    assert_eq!(result, expected);
+                    assert_eq!(data.next_index, POPULATION.len() - (to_prune_index + 1));
+                }
+
+                #[test]
+                fn chunked_prune() {
+                    for index in 0..POPULATION.len() {
+                        test_pruning(index, |data, index| assert!(data.prune(index).is_ok()));
+                    }
+                }
+
+                #[test]
+                fn prune_at_most() {
+                    for index in 0..POPULATION.len() {
+                        test_pruning(index, |data, index| {
+                            assert_eq!(data.prune_at_most(index), index + 1)
+                        });
+                    }
+                }
+
+                #[test]
+                fn prune_all() {
+                    let mut data = Data::default();
+                    data.prune_all();
+                    assert_eq!(data.length(), 0);
+
+                    test_populate(&mut data);
+                    let capacity = data.chunks.capacity();
+
+                    data.prune_all();
+                    assert!(data.no_elements());
+                    assert_eq!(data.length(), 0);
+                    assert_eq!(data.num_elements(), 0);
+                    assert_eq!(data.chunks.capacity(), capacity);
+
+                    data.push(1);
+                    data.push(2);
+                    assert_eq!(
+                        data.iter_with_index().collect::<Vec<_>>(),
+                        vec![(0, &1), (1, &2)]
+                    );
+                    assert_eq!(data.chunks.len(), 1);
+                    assert_eq!(data.chunks.capacity(), capacity);
+                }
+
+                #[test]
+                fn prune_at_most_clamps() {
+                    let mut data = Data::default();
+                    assert_eq!(data.prune_at_most(0), 0);
+                    assert_eq!(data.prune_at_most(5), 0);
+                    assert_eq!(data.length(), 0);
+
+                    test_populate(&mut data);
+                    data.try_push(None);
+                    assert_eq!(data.prune_at_most(100), POPULATION.len() + 1);
+                    assert_eq!(data.length(), 0);
+                    assert!(data.no_elements());
+
+                    // Only gaps, which `prune` would reject.
+                    data.try_push(None);
+                    data.try_push(None);
+                    assert!(data.prune(0).is_err());
+                    assert_eq!(data.prune_at_most(0), 1);
+                    assert_eq!(data.length(), 1);
+
+                    data.push(1);
+                    assert_eq!(data.iter_with_index().collect::<Vec<_>>(), vec![(1, &1)]);
+                }
+
+                /// Handle if we try and prune something empty.
+                #[test]
+                fn chunked_prune_empty() {
+                    let mut data = Data::default();
+                    assert!(data.prune(0).is_err());
+                }
+
+                /// Handle if we try to clear an index past the index stored.
+                #[test]
+                fn chunked_prune_past_index() {
+                    let mut data = Data::default();
+                    test_populate(&mut data);
+
+                    assert!(data.prune(10).is_err());
+                }
+
+                #[test]
+                fn chunked_prune_without_curr() {
+                    let mut data = Data::default();
+                    test_populate(&mut data);
+                    data.try_push(None);
+
+                    assert!(data.prune(10).is_ok());
+                }
+
+                #[test]
+                fn prune_zero_when_none() {
+                    let mut data = Data::default();
+                    data.try_push(None);
+                    data.try_push(None);
+                    data.try_push(None);
+                    test_populate(&mut data);
+
+                    assert!(data.prune(0).is_ok());
+                    assert_eq!(data.chunks[0].start_offset, 2);
+                    assert_eq!(data.chunks[1].start_offset, 8);
+                    assert_eq!(data.next_index, POPULATION.len() + 3 - 1);
+
+                    assert!(data.prune(0).is_ok());
+                    assert_eq!(data.chunks[0].start_offset, 1);
+                    assert_eq!(data.chunks[1].start_offset, 7);
+                    assert_eq!(data.next_index, POPULATION.len() + 3 - 2);
+
+                    assert!(data.prune(0).is_ok());
+                    assert_eq!(data.chunks[0].start_offset, 0);
+                    assert_eq!(data.chunks[1].start_offset, 6);
+                    assert_eq!(data.next_index, POPULATION.len() + 3 - 3);
+
+                    assert!(data.prune(0).is_ok());
+                    assert_eq!(data.chunks[0].start_offset, 0);
+                    assert_eq!(data.chunks[0].data.as_slice(), &[2, 3]);
+                    assert_eq!(data.chunks[1].start_offset, 5);
+                    assert_eq!(data.next_index, POPULATION.len() + 3 - 4);
+                }
+
+                #[test]
+                fn prune_zero_leading_gap() {
+                    let mut data = Data::default();
+                    data.try_push(None);
+                    data.try_push(Some(1));
+
+                    assert!(data.prune(0).is_ok());
+                    assert_eq!(data.chunks.len(), 1);
+                    assert_eq!(data.chunks[0].start_offset, 0);
+                    assert_eq!(data.chunks[0].data.as_slice(), &[1]);
+                    assert_eq!(data.length(), 1);
+
+                    data.push(2);
+                    assert_eq!(
+                        data.iter_with_index().collect::<Vec<_>>(),
+                        vec![(0, &1), (1, &2)]
+                    );
+                }
+
+                /// Pruning inside a first chunk that doesn't start at zero should shift the
+                /// later chunks by everything removed, including the leading gap.
+                #[test]
+                fn prune_inside_offset_first_chunk() {
+                    let mut data = Data::default();
+                    data.try_push(None);
+                    data.try_push(None);
+                    data.try_push(None);
+                    test_populate(&mut data);
+
+                    assert!(data.prune(3).is_ok());
+                    assert!(data.validate().is_ok());
+                    assert_eq!(data.chunks[0].start_offset, 0);
+                    assert_eq!(data.chunks[0].data.as_slice(), &[2, 3]);
+                    assert_eq!(data.chunks[1].start_offset, 5);
+                    assert_eq!(data.length(), POPULATION.len() - 1);
+                    assert_eq!(
+                        data.iter_with_index().collect::<Vec<_>>(),
+                        vec![(0, &2), (1, &3), (5, &7), (6, &8), (7, &9), (8, &10)]
+                    );
+                    assert_eq!(data.get(5), Some(&7));
+                    assert_eq!(data.get(4), None);
+
+                    // Pruning the rest of the first chunk along with part of the gap.
+                    let mut data = Data::default();
+                    data.try_push(None);
+                    data.try_push(None);
+                    data.try_push(None);
+                    test_populate(&mut data);
+
+                    assert!(data.prune(7).is_ok());
+                    assert!(data.validate().is_ok());
+                    assert_eq!(data.chunks.len(), 1);
+                    assert_eq!(data.chunks[0].start_offset, 1);
+                    assert_eq!(data.get(1), Some(&7));
+                    assert_eq!(data.last(), Some(&10));
+                }
+
+                #[test]
+                fn prune_in_leading_gap() {
+                    let mut data = Data::default();
+                    data.try_push(None);
+                    data.try_push(None);
+                    data.try_push(None);
+                    test_populate(&mut data);
+
+                    assert!(data.prune(1).is_ok());
+                    assert!(data.validate().is_ok());
+                    assert_eq!(data.chunks[0].start_offset, 1);
+                    assert_eq!(data.chunks[1].start_offset, 7);
+                    assert_eq!(data.first(), Some(&1));
+                    assert_eq!(data.get(1), Some(&1));
+                    assert_eq!(data.get(7), Some(&7));
+                    assert_eq!(data.length(), POPULATION.len() + 1);
+                }
+
+                /// Pruning a whole chunk can leave an empty chunk sharing its start offset with
+                /// the next one, which shouldn't trip up later prunes.
+                #[test]
+                fn prune_after_emptied_chunk() {
+                    for len in 1..10 {
+                        let mut data = Data::default();
+                        for item in 0..len {
+                            data.push(item);
+                            data.insert_break();
+                        }
+
+                        for pruned in 1..=len {
+                            assert!(data.prune(0).is_ok(), "pruning {pruned} of {len}");
+                            assert_eq!(data.length(), (len - pruned) as usize);
+                            assert_eq!(data.first(), (pruned < len).then_some(&pruned));
+                        }
+                    }
+                }
+
+                #[test]
+                fn first_last() {
+                    let mut data = Data::default();
+                    test_populate(&mut data);
+
+                    assert_eq!(data.first(), Some(&1));
+                    assert_eq!(data.last(), Some(&10));
+                }
+
+                #[test]
+                fn latest_run() {
+                    let mut data = Data::default();
+                    assert_eq!(data.latest_run(), None);
+                    assert_eq!(data.iter_latest_run().next(), None);
+                    assert_eq!(data.last_chunk_data(), None);
+                    assert_eq!(data.last_chunk_start_offset(), None);
+
+                    test_populate(&mut data);
+                    assert_eq!(data.latest_run(), Some((6, &[7, 8, 9, 10][..])));
+
+                    // Still the latest run after being sealed.
+                    data.insert_break();
+                    data.try_push(None);
+                    assert_eq!(data.latest_run(), Some((6, &[7, 8, 9, 10][..])));
+                    assert_eq!(
+                        data.iter_latest_run().collect::<Vec<_>>(),
+                        vec![(6, &7), (7, &8), (8, &9), (9, &10)]
+                    );
+
+                    data.push(12);
+                    assert_eq!(data.latest_run(), Some((11, &[12][..])));
+                    assert_eq!(data.last_chunk_data(), Some(&[12][..]));
+                    assert_eq!(data.last_chunk_start_offset(), Some(11));
+
+                    let iter = data.iter_latest_run();
+                    assert_eq!(iter.len(), 1);
+                    assert_eq!(iter.rev().collect::<Vec<_>>(), vec![(11, &12)]);
+                }
+
+                #[test]
+                fn iter_chunks_rev() {
+                    let mut data = Data::default();
+                    assert_eq!(data.iter_chunks_rev().next(), None);
+                    assert_eq!(data.rev_elements_until(5).next(), None);
+
+                    test_populate(&mut data);
+                    assert_eq!(
+                        data.iter_chunks_rev().collect::<Vec<_>>(),
+                        vec![(6, &[7, 8, 9, 10][..]), (0, &[1, 2, 3][..])]
+                    );
+
+                    let iter = data.rev_elements_until(5);
+                    assert_eq!(iter.len(), 5);
+                    assert_eq!(
+                        iter.collect::<Vec<_>>(),
+                        vec![(9, &10), (8, &9), (7, &8), (6, &7), (2, &3)]
+                    );
+
+                    let iter = data.rev_elements_until(100);
+                    assert_eq!(iter.len(), 7);
+                    assert_eq!(iter.count(), 7);
+                    assert_eq!(data.rev_elements_until(0).next(), None);
+                }
+
+                /// If the latest chunk has enough elements, older chunks shouldn't be read.
+                #[test]
+                fn rev_elements_until_stays_in_last_chunk() {
+                    let mut data = Data::default();
+                    test_populate(&mut data);
+
+                    let last = data.last_chunk_data().unwrap().as_ptr_range();
+                    let elements = data.rev_elements_until(4).collect::<Vec<_>>();
+                    assert_eq!(elements.len(), 4);
+                    assert!(
+                        elements
+                            .iter()
+                            .all(|(_, datum)| last.contains(&std::ptr::from_ref(*datum)))
+                    );
+                }
+
+                #[test]
+                fn rolling_window_iter() {
+                    let mut data = Data::default();
+                    assert_eq!(data.rolling_window_iter(4).next(), None);
+
+                    data.extend([1, 2, 3, 4, 5]);
+                    data.try_push(None);
+                    data.extend([7, 8, 9]);
+
+                    assert_eq!(
+                        data.rolling_window_iter(4).collect::<Vec<_>>(),
+                        vec![&[1, 2, 3, 4][..], &[2, 3, 4, 5], &[7, 8, 9]]
+                    );
+                    assert_eq!(
+                        data.rolling_window_iter(3).rev().collect::<Vec<_>>(),
+                        vec![&[7, 8, 9][..], &[3, 4, 5], &[2, 3, 4], &[1, 2, 3]]
+                    );
+                    assert_eq!(data.rolling_window_iter(1).count(), 8);
+                    assert_eq!(
+                        data.rolling_window_iter(100).collect::<Vec<_>>(),
+                        vec![&[1, 2, 3, 4, 5][..], &[7, 8, 9]]
+                    );
+                }
+
+                #[test]
+                fn iter() {
+                    let mut data = Data::default();
+                    test_populate(&mut data);
+
+                    assert_eq!(
+                        data.into_owned_iter().collect::<Vec<_>>(),
+                        POPULATION.iter().filter_map(|v| *v).collect::<Vec<_>>(),
+                    );
+                }
+
+                #[test]
+                fn reverse_iter() {
+                    let mut data = Data::default();
+                    test_populate(&mut data);
+
+                    assert_eq!(
+                        data.into_owned_iter().rev().collect::<Vec<_>>(),
+                        POPULATION
+                            .iter()
+                            .filter_map(|v| *v)
+                            .rev()
+                            .collect::<Vec<_>>(),
+                    );
+                }
+
+                /// Reversing should yield the indices in descending order, rather than
+                /// just the values.
+                #[test]
+                fn reverse_iter_with_index() {
+                    let mut data = Data::default();
+                    data.try_push(None);
+                    test_populate(&mut data);
+                    data.try_push(None);
+
+                    let reversed = data.iter_with_index().rev().collect::<Vec<_>>();
+                    assert_eq!(
+                        reversed,
+                        vec![
+                            (10, &10),
+                            (9, &9),
+                            (8, &8),
+                            (7, &7),
+                            (3, &3),
+                            (2, &2),
+                            (1, &1)
+                        ]
+                    );
+                    assert!(reversed.windows(2).all(|w| w[0].0 > w[1].0));
+
+                    // Every index should still point at its element.
+                    assert!(
+                        reversed
+                            .iter()
+                            .all(|&(index, datum)| data.get(index) == Some(datum))
+                    );
+                }
+
+                #[test]
+                fn into_iter_with_index() {
+                    let mut data = Data::default();
+                    test_populate(&mut data);
+
+                    let expected = data
+                        .iter_with_index()
+                        .map(|(index, datum)| (index, *datum))
+                        .collect::<Vec<_>>();
+
+                    let iter = data.clone().into_iter_with_index();
+                    assert_eq!(iter.len(), expected.len());
+                    assert_eq!(iter.collect::<Vec<_>>(), expected);
+
+                    assert_eq!(
+                        data.into_iter_with_index().rev().collect::<Vec<_>>(),
+                        expected.into_iter().rev().collect::<Vec<_>>()
+                    );
+                }
+
+                #[test]
+                fn extend_from_slice() {
+                    let mut pushed = Data::default();
+                    let mut extended = Data::default();
+
+                    for data in [&mut pushed, &mut extended] {
+                        data.push(1);
+                        data.try_push(None);
+                    }
+
+                    let items = [2, 3, 4, 5, 6];
+                    for item in items {
+                        pushed.push(item);
+                    }
+                    extended.extend_from_slice(&items);
+
+                    assert_eq!(pushed, extended);
+                    assert_eq!(extended.chunks.len(), 2);
+                    assert!(extended.is_active);
+
+                    // This should continue the active chunk.
+                    extended.extend_from_slice(&[7, 8]);
+                    extended.extend_from_slice(&[]);
+                    assert_eq!(extended.chunks.len(), 2);
+                    assert_eq!(extended.length(), 9);
+                    assert_eq!(extended.chunks[1].data.as_slice(), &[2, 3, 4, 5, 6, 7, 8]);
+
+                    // An empty slice should not start a new chunk.
+                    extended.insert_break();
+                    extended.extend_from_slice(&[]);
+                    assert_eq!(extended.chunks.len(), 2);
+                    assert_eq!(extended.length(), 9);
+                }
+
+                #[test]
+                fn copy_from_slice() {
+                    let mut extended = Data::default();
+                    let mut copied = Data::default();
+
+                    for data in [&mut extended, &mut copied] {
+                        data.push(1);
+                        data.try_push(None);
+                    }
+
+                    // Enough to spill out of any inline storage.
+                    let items = (2..20).collect::<Vec<_>>();
+                    extended.extend_from_slice(&items);
+                    copied.copy_from_slice(&items);
+                    copied.copy_from_slice(&[]);
+
+                    assert_eq!(extended, copied);
+                    assert_eq!(copied.chunks.len(), 2);
+                    assert_eq!(copied.num_elements(), 19);
+                    assert!(copied.validate().is_ok());
+                }
+
+                #[test]
+                fn extend_from_vec() {
+                    let mut pushed = Data::default();
+                    let mut extended = Data::default();
+
+                    for item in [1, 2, 3] {
+                        pushed.push(item);
+                    }
+                    pushed.try_push(None);
+                    for item in [4, 5] {
+                        pushed.push(item);
+                    }
+
+                    extended.extend_from_vec(vec![1, 2]);
+                    extended.extend_from_vec(vec![3]);
+                    extended.try_push(None);
+                    extended.extend_from_vec(vec![]);
+                    extended.extend_from_vec(vec![4, 5]);
+
+                    assert_eq!(pushed, extended);
+                    assert_eq!(extended.chunks.len(), 2);
+                }
+
+                #[test]
+                fn extend() {
+                    let mut pushed = Data::default();
+                    let mut extended = Data::default();
+
+                    for data in [&mut pushed, &mut extended] {
+                        data.push(1);
+                        data.try_push(None);
+                    }
+
+                    for item in 2..6 {
+                        pushed.push(item);
+                    }
+                    extended.extend(2..6);
+
+                    assert_eq!(pushed, extended);
+                    assert_eq!(extended.chunks.len(), 2);
+                    assert_eq!(extended.chunks[1].start_offset, 2);
+                    assert!(extended.is_active);
+
+                    // This should continue the active chunk, and work without an exact size hint.
+                    extended.extend((6..20).filter(|item| item % 2 == 0));
+                    extended.extend(std::iter::empty::<u64>());
+                    assert_eq!(extended.chunks.len(), 2);
+                    assert_eq!(extended.length(), 13);
+                    assert_eq!(extended.last(), Some(&18));
+
+                    // An empty iterator should not start a new chunk.
+                    extended.insert_break();
+                    extended.extend(std::iter::empty::<u64>());
+                    assert_eq!(extended.chunks.len(), 2);
+                    assert_eq!(extended.length(), 13);
+
+                    extended.extend([20]);
+                    assert_eq!(extended.chunks.len(), 3);
+                    assert_eq!(extended.chunks[2].start_offset, 13);
+                }
+
+                #[test]
+                fn extend_with_gaps() {
+                    let mut data = Data::default();
+                    data.extend(POPULATION);
+
+                    let mut expected = Data::default();
+                    test_populate(&mut expected);
+                    assert_eq!(data, expected);
+                    assert_eq!(data.chunks.len(), 2);
+                    assert_eq!(data.num_elements(), 7);
+
+                    // This should continue the active chunk.
+                    data.extend([Some(11), Some(12)]);
+                    data.extend(std::iter::empty::<Option<u64>>());
+                    assert_eq!(data.chunks.len(), 2);
+                    assert_eq!(data.length(), 12);
+
+                    data.extend([None, Some(14)]);
+                    assert_eq!(data.chunks.len(), 3);
+                    assert_eq!(data.chunks[2].start_offset, 13);
+                    assert!(data.validate().is_ok());
+                }
+
+                #[test]
+                fn from_vec_and_slice() {
+                    let items = vec![1, 2, 3, 4];
+
+                    for data in [Data::from(items.clone()), Data::from(items.as_slice())] {
+                        assert_eq!(data.length(), 4);
+                        assert_eq!(data.chunks.len(), 1);
+                        assert_eq!(data.chunks[0].start_offset, 0);
+                        assert!(data.iter().eq(items.iter()));
+                    }
+
+                    let mut data = Data::from(Vec::new());
+                    assert!(data.chunks.is_empty());
+                    assert_eq!(data, Data::from(&[][..]));
+
+                    data.push(1);
+                    assert_eq!(data.iter_with_index().collect::<Vec<_>>(), vec![(0, &1)]);
+                }
+
+                #[test]
+                fn apply() {
+                    let mut data = Data::default();
+                    data.apply(|_| unreachable!());
+                    assert_eq!(data, Data::default());
+
+                    // Single-element chunks.
+                    for p in [Some(1), None, Some(2), None, Some(3)] {
+                        data.try_push(p);
+                    }
+                    let mut seen = Vec::new();
+                    data.apply(|item| {
+                        seen.push(*item);
+                        *item *= 10;
+                    });
+                    assert_eq!(seen, vec![1, 2, 3]);
+                    assert_eq!(
+                        data.iter_with_index().collect::<Vec<_>>(),
+                        vec![(0, &10), (2, &20), (4, &30)]
+                    );
+
+                    // Normalize against the maximum.
+                    let mut data = Data::default();
+                    test_populate(&mut data);
+                    let max = *data.iter().max().unwrap();
+                    data.apply(|item| *item = max - *item);
+
+                    let mut expected = Data::default();
+                    for p in POPULATION {
+                        expected.try_push(p.map(|p| max - p));
+                    }
+                    assert_eq!(data, expected);
+                }
+
+                #[test]
+                fn chunks_mut() {
+                    let mut data = Data::default();
+                    assert_eq!(data.chunks_mut().next(), None);
+
+                    test_populate(&mut data);
+
+                    let chunks = data.chunks_mut();
+                    assert_eq!(chunks.len(), 2);
+
+                    for chunk in chunks {
+                        let first = chunk[0];
+                        for item in chunk.iter_mut() {
+                            *item -= first;
+                        }
+                    }
+
+                    assert_eq!(
+                        data.iter_with_index().collect::<Vec<_>>(),
+                        vec![
+                            (0, &0),
+                            (1, &1),
+                            (2, &2),
+                            (6, &0),
+                            (7, &1),
+                            (8, &2),
+                            (9, &3)
+                        ]
+                    );
+
+                    let last = data.chunks_mut().next_back().unwrap();
+                    last.reverse();
+                    assert_eq!(data.latest_run(), Some((6, &[3, 2, 1, 0][..])));
+                    assert_eq!(data.length(), 10);
+                }
+
+                #[test]
+                fn drain() {
+                    for start in 0..=POPULATION.len() {
+                        for end in start..=POPULATION.len() {
+                            let mut data = Data::default();
+                            test_populate(&mut data);
+
+                            let drained = data.drain(start..end).collect::<Vec<_>>();
+                            let expected_drained = POPULATION
+                                .into_iter()
+                                .enumerate()
+                                .filter_map(|(a, b)| b.map(|b| (a, b)))
+                                .filter(|(a, _)| (start..end).contains(a))
+                                .collect::<Vec<_>>();
+                            assert_eq!(drained, expected_drained, "drained {start}..{end}");
+
+                            let mut expected = Data::default();
+                            for p in POPULATION[..start].iter().chain(&POPULATION[end..]) {
+                                expected.try_push(*p);
+                            }
+                            assert_eq!(data, expected, "remaining after draining {start}..{end}");
+                            assert_eq!(data.num_elements(), expected.num_elements());
+                            assert!(data.chunks.iter().all(|c| !c.data.is_empty()));
+
+                            // Pushing afterwards should continue from the new length.
+                            data.push(11);
+                            expected.push(11);
+                            assert_eq!(data, expected, "pushing after draining {start}..{end}");
+                        }
+                    }
+                }
+
+                #[test]
+                fn drain_within_chunk() {
+                    let mut data = Data::default();
+                    test_populate(&mut data);
+
+                    let drained = data.drain(7..9).collect::<Vec<_>>();
+                    assert_eq!(drained, vec![(7, 8), (8, 9)]);
+                    assert_eq!(data.chunks.len(), 2);
+                    assert_eq!(data.chunks[1].start_offset, 6);
+                    assert_eq!(data.chunks[1].data.as_slice(), &[7, 10]);
+                    assert_eq!(data.length(), 8);
+                }
+
+                #[test]
+                #[should_panic(expected = "range end 11 out of range for length 10")]
+                fn drain_out_of_range() {
+                    let mut data = Data::default();
+                    test_populate(&mut data);
+
+                    data.drain(5..11);
+                }
+
+                #[test]
+                fn count_in_range() {
+                    let mut data = Data::default();
+                    test_populate(&mut data);
+
+                    for start in 0..=POPULATION.len() + 1 {
+                        for end in start..=POPULATION.len() + 1 {
+                            let expected = POPULATION
+                                .get(start..end.min(POPULATION.len()))
+                                .map_or(0, |p| p.iter().flatten().count());
+                            assert_eq!(
+                                data.count_in_range(start..end),
+                                expected,
+                                "counting {start}..{end}"
+                            );
+                        }
+                    }
+                }
+
+                #[test]
+                fn drain_front() {
+                    for n in 0..=POPULATION.len() {
+                        let mut data = Data::default();
+                        test_populate(&mut data);
+                        let cursor = data.cursor_at(n);
+
+                        let drained = data.drain_front(n);
+                        assert_eq!(drained.len(), POPULATION[..n].iter().flatten().count());
+                        assert_eq!(
+                            drained.collect::<Vec<_>>(),
+                            POPULATION[..n]
+                                .iter()
+                                .flatten()
+                                .copied()
+                                .collect::<Vec<_>>(),
+                            "draining {n}"
+                        );
+
+                        let mut pruned = Data::default();
+                        test_populate(&mut pruned);
+                        if n > 0 {
+                            pruned.prune(n - 1).unwrap();
+                        }
+                        assert_eq!(data, pruned, "remaining after draining {n}");
+                        assert_eq!(data.num_elements(), pruned.num_elements());
+                        assert_eq!(cursor.index(&data), Some(0));
+                        assert!(data.validate().is_ok());
+                    }
+                }
+
+                #[test]
+                #[should_panic(expected = "drain count 11 out of range for length 10")]
+                fn drain_front_out_of_range() {
+                    let mut data = Data::default();
+                    test_populate(&mut data);
+
+                    data.drain_front(11);
+                }
+
+                #[test]
+                fn rotate() {
+                    for n in 0..=POPULATION.len() {
+                        let mut expected = POPULATION;
+                        expected.rotate_left(n);
+
+                        let mut data = Data::default();
+                        test_populate(&mut data);
+                        data.rotate_left(n);
+                        assert_eq!(
+                            data.iter_with_gaps()
+                                .map(|v| v.copied())
+                                .collect::<Vec<_>>(),
+                            expected,
+                            "rotating left by {n}"
+                        );
+                        assert_eq!(data.num_elements(), 7);
+                        assert!(data.validate().is_ok());
+
+                        // Pushing afterwards should continue from the length.
+                        data.push(11);
+                        assert_eq!(data.get(10), Some(&11));
+                        assert!(data.validate().is_ok());
+
+                        let mut expected = POPULATION;
+                        expected.rotate_right(n);
+
+                        let mut data = Data::default();
+                        test_populate(&mut data);
+                        data.rotate_right(n);
+                        assert_eq!(
+                            data.iter_with_gaps()
+                                .map(|v| v.copied())
+                                .collect::<Vec<_>>(),
+                            expected,
+                            "rotating right by {n}"
+                        );
+                        assert!(data.validate().is_ok());
+                    }
+                }
+
+                #[test]
+                fn remove() {
+                    for index in 0..POPULATION.len() + 1 {
+                        let mut data = Data::default();
+                        test_populate(&mut data);
+
+                        let expected_removed = POPULATION.get(index).copied().flatten();
+                        assert_eq!(data.remove(index), expected_removed, "removing {index}");
+
+                        let mut expected = Data::default();
+                        for (i, p) in POPULATION.into_iter().enumerate() {
+                            expected.try_push(if i == index { None } else { p });
+                        }
+                        assert_eq!(data, expected, "remaining after removing {index}");
+                        assert_eq!(data.length(), POPULATION.len());
+                        assert!(data.chunks.iter().all(|c| !c.data.is_empty()));
+
+                        // Pushing afterwards should continue from the same length.
+                        data.push(11);
+                        expected.push(11);
+                        assert_eq!(data, expected, "pushing after removing {index}");
+                    }
+                }
+
+                #[test]
+                fn remove_interior_splits() {
+                    let mut data = Data::default();
+                    test_populate(&mut data);
+
+                    assert_eq!(data.remove(7), Some(8));
+                    assert_eq!(data.chunks.len(), 3);
+                    assert_eq!(data.chunks[1].start_offset, 6);
+                    assert_eq!(data.chunks[1].data.as_slice(), &[7]);
+                    assert_eq!(data.chunks[2].start_offset, 8);
+                    assert_eq!(data.chunks[2].data.as_slice(), &[9, 10]);
+                    assert_eq!(data.num_elements(), 6);
+                    assert_eq!(data.length(), 10);
+
+                    // Removing the same index again is now a gap.
+                    assert_eq!(data.remove(7), None);
+                }
+
+                #[test]
+                fn remove_empties_chunk() {
+                    let mut data = Data::default();
+                    data.push(1);
+                    data.try_push(None);
+                    data.push(3);
+
+                    assert_eq!(data.remove(2), Some(3));
+                    assert_eq!(data.chunks.len(), 1);
+                    assert_eq!(data.length(), 3);
+                    assert!(!data.is_active);
+
+                    assert_eq!(data.remove(0), Some(1));
+                    assert!(data.chunks.is_empty());
+                    assert!(data.no_elements());
+                    assert_eq!(data.length(), 3);
+
+                    data.push(4);
+                    assert_eq!(data.chunks[0].start_offset, 3);
+                }
+
+                #[test]
+                fn prune_and_shrink_removes_empty_chunks() {
+                    let mut data = Data::default();
+                    data.push(1);
+                    data.push(2);
+                    data.try_push(None);
+                    data.push(4);
+
+                    // Pruning alone never leaves behind a chunk with no data.
+                    let mut pruned = data.clone();
+                    assert!(pruned.prune(1).is_ok());
+                    assert!(pruned.chunks.iter().all(|chunk| !chunk.data.is_empty()));
+                    assert!(pruned.prune(1).is_ok());
+                    assert!(pruned.chunks.is_empty());
+
+                    assert!(data.prune_and_shrink_to_fit(1).is_ok());
+                    assert_eq!(data.chunks.len(), 1);
+                    assert_eq!(data.chunks[0].start_offset, 1);
+                    assert_eq!(data.first(), Some(&4));
+                    assert_eq!(data.length(), 2);
+
+                    // Emptying the last chunk should also seal it.
+                    assert!(data.prune_and_shrink_to_fit(1).is_ok());
+                    assert!(data.chunks.is_empty());
+                    assert!(data.no_elements());
+
+                    data.push(5);
+                    assert_eq!(data.iter_with_index().collect::<Vec<_>>(), vec![(0, &5)]);
+                }
+
+                #[test]
+                fn set() {
+                    let mut data = Data::default();
+                    test_populate(&mut data);
+
+                    assert_eq!(data.set(1, 20), Some(2));
+                    assert_eq!(data.get(1), Some(&20));
+                    assert_eq!(data.set(4, 5), None);
+                    assert_eq!(data.get(4), None);
+                    assert_eq!(data.set(10, 11), None);
+                    assert_eq!(data.length(), 10);
+                    assert_eq!(data.num_elements(), 7);
+                }
+
+                #[test]
+                fn fill_adjacent() {
+                    let mut data = Data::default();
+                    test_populate(&mut data);
+
+                    // Extends the end of the first chunk.
+                    assert_eq!(data.fill(3, 4), Ok(None));
+                    assert_eq!(data.chunks[0].data.as_slice(), &[1, 2, 3, 4]);
+
+                    // Extends the start of the second chunk.
+                    assert_eq!(data.fill(5, 6), Ok(None));
+                    assert_eq!(data.chunks[1].start_offset, 5);
+                    assert_eq!(data.chunks[1].data.as_slice(), &[6, 7, 8, 9, 10]);
+
+                    // Bridges the two chunks, merging them.
+                    assert_eq!(data.fill(4, 5), Ok(None));
+                    assert_eq!(data.chunks.len(), 1);
+                    assert_eq!(
+                        data.chunks[0].data.as_slice(),
+                        &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]
+                    );
+
+                    // Overwrites existing values.
+                    assert_eq!(data.fill(4, 50), Ok(Some(5)));
+                    assert_eq!(data.length(), 10);
+                    assert_eq!(data.num_elements(), 10);
+
+                    data.push(11);
+                    assert_eq!(data.chunks.len(), 1);
+                    assert_eq!(data.last(), Some(&11));
+                }
+
+                #[test]
+                fn fill_isolated() {
+                    let mut data = Data::default();
+                    data.try_push(None);
+                    data.try_push(None);
+                    data.try_push(None);
+                    data.push(4);
+                    for _ in 0..3 {
+                        data.try_push(None);
+                    }
+
+                    assert_eq!(data.fill(1, 2), Ok(None));
+                    assert_eq!(data.fill(5, 6), Ok(None));
+                    assert_eq!(data.chunks.len(), 3);
+                    assert_eq!(
+                        data.iter_with_index().collect::<Vec<_>>(),
+                        vec![(1, &2), (3, &4), (5, &6)]
+                    );
+                    assert_eq!(data.first(), Some(&2));
+                    assert_eq!(data.last(), Some(&6));
+                    assert_eq!(data.length(), 7);
+
+                    // The trailing gap is still a gap, so new pushes start a new chunk.
+                    data.push(8);
+                    assert_eq!(data.chunks.len(), 4);
+                    assert_eq!(data.chunks[3].start_offset, 7);
+                }
+
+                /// The cached element count should match the chunks after every change.
+                #[test]
+                fn num_elements_tracks_changes() {
+                    fn check(data: &Data) {
+                        assert!(data.validate().is_ok());
+                        assert_eq!(data.num_elements(), data.iter().count());
+                        assert_eq!(data.no_elements(), data.iter().next().is_none());
+                    }
+
+                    let mut data = Data::default();
+                    check(&data);
+
+                    data.try_push(None);
+                    test_populate(&mut data);
+                    check(&data);
+
+                    data.extend_from_slice(&[11, 12]);
+                    data.extend([13, 14]);
+                    data.insert_break();
+                    data.extend_from_vec(vec![15]);
+                    check(&data);
+
+                    assert_eq!(data.fill(5, 5), Ok(None));
+                    assert_eq!(data.fill(5, 5), Ok(Some(5)));
+                    check(&data);
+
+                    assert_eq!(data.remove(2), Some(2));
+                    assert_eq!(data.remove(2), None);
+                    check(&data);
+
+                    assert_eq!(data.drain(6..9).count(), 2);
+                    check(&data);
+
+                    assert!(data.prune(1).is_ok());
+                    check(&data);
+
+                    let length = data.length();
+                    assert_eq!(data.prune_at_most(100), length);
+                    check(&data);
+                    assert!(data.no_elements());
+
+                    data.push(1);
+                    check(&data);
+                    data.prune_all();
+                    check(&data);
+                }
+
+                #[test]
+                fn fill_out_of_range() {
+                    let mut data = Data::default();
+                    assert_eq!(
+                        data.fill(0, 1),
+                        Err(IndexOutOfRangeError {
+                            index: 0,
+                            length: 0
+                        })
+                    );
+
+                    test_populate(&mut data);
+                    assert_eq!(
+                        data.fill(10, 1),
+                        Err(IndexOutOfRangeError {
+                            index: 10,
+                            length: 10
+                        })
+                    );
+                    assert_eq!(data.length(), 10);
+                }
+
+                #[track_caller]
+                fn base_slice_test(
+                    data: &Data, base_slice: &[u64], expected: &[(u64, u64)],
+                    base_slice_index: usize, expected_slice_index: usize,
+                ) {
+                    let base_slice = &base_slice[0..base_slice_index];
+
+                    assert_eq!(
+                        data.iter_along_base(base_slice).len(),
+                        expected_slice_index,
+                        "the returned size of the iterator should match"
+                    );
+                    assert_eq!(
+                        data.iter_along_base(base_slice)
+                            .map(|(a, b)| (*a, *b))
+                            .collect::<Vec<_>>(),
+                        expected[0..expected_slice_index],
+                        "the actual generated values should match"
+                    );
+                }
+
+                #[test]
+                fn base_slice_simple() {
+                    let mut data = Data::default();
+                    data.push(1);
+                    data.push(2);
+                    data.push(3);
+
+                    let base_slice = [1, 2, 3, 4, 5];
+                    let expected = [(1, 1), (2, 2), (3, 3)];
+
+                    // Test with a larger base slice
+                    base_slice_test(&data, &base_slice, &expected, 5, 3);
+
+                    // Test with an exact base slice
+                    base_slice_test(&data, &base_slice, &expected, 3, 3);
+
+                    // Test with a smaller base slice
+                    base_slice_test(&data, &base_slice, &expected, 2, 2);
+                    base_slice_test(&data, &base_slice, &expected, 1, 1);
+                    base_slice_test(&data, &base_slice, &expected, 0, 0);
+                }
+
+                #[test]
+                fn base_slice_exact_length() {
+                    let mut data = Data::default();
+                    test_populate(&mut data);
+                    data.try_push(None);
+
+                    let base_slice = (0..data.length() as u64).collect::<Vec<_>>();
+                    let expected = data
+                        .iter_with_index()
+                        .map(|(index, datum)| (index as u64, *datum))
+                        .collect::<Vec<_>>();
+
+                    base_slice_test(&data, &base_slice, &expected, data.length(), 7);
+                }
+
+                #[test]
+                fn base_slice_chunked() {
+                    let mut data = Data::default();
+                    data.push(1);
+                    data.push(2);
+                    data.push(3);
+                    data.try_push(None);
+                    data.try_push(None);
+                    data.try_push(None);
+                    data.push(7);
+                    data.push(8);
+                    data.push(9);
+
+                    let base_slice = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+                    let expected = [(1, 1), (2, 2), (3, 3), (7, 7), (8, 8), (9, 9)];
+
+                    // Test with a larger base slice
+                    base_slice_test(&data, &base_slice, &expected, 10, 6);
+
+                    // Test with an exact base slice
+                    base_slice_test(&data, &base_slice, &expected, 9, 6);
+
+                    // Test with a smaller base slice
+                    base_slice_test(&data, &base_slice, &expected, 8, 5);
+                    base_slice_test(&data, &base_slice, &expected, 7, 4);
+                    base_slice_test(&data, &base_slice, &expected, 6, 3);
+                    base_slice_test(&data, &base_slice, &expected, 5, 3);
+                    base_slice_test(&data, &base_slice, &expected, 4, 3);
+                    base_slice_test(&data, &base_slice, &expected, 3, 3);
+                    base_slice_test(&data, &base_slice, &expected, 2, 2);
+                    base_slice_test(&data, &base_slice, &expected, 1, 1);
+                    base_slice_test(&data, &base_slice, &expected, 0, 0);
+                }
+            }
+        };
+    }
+
+    storage_tests!(vec_storage, Vec<u64>);
+
+    /// [`Default`] shouldn't require `D: Default`.
+    #[test]
+    fn default_without_default_data() {
+        #[derive(Debug, PartialEq)]
+        struct NoDefault(u64);
+
+        let mut data = ChunkedData::<NoDefault>::default();
+        data.push(NoDefault(1));
+        assert_eq!(data.first(), Some(&NoDefault(1)));
+
+        let data = ChunkedData::<String>::default();
+        assert_eq!(data.length(), 0);
+
+        let data = crate::data::RleChunkedData::<NoDefault>::default();
+        assert!(data.no_elements());
+    }
+
+    #[test]
+    fn prune_errors() {
+        let mut data = ChunkedData::<u64>::default();
+        assert_eq!(data.prune(0), Err(PruneError::Empty));
+
+        data.try_push(None);
+        assert_eq!(data.prune(0), Err(PruneError::Empty));
+
+        test_populate(&mut data);
+        assert_eq!(
+            data.prune(11),
+            Err(PruneError::IndexOutOfBounds {
+                requested: 11,
+                max: 10
+            })
+        );
+        assert_eq!(
+            data.prune_and_shrink_to_fit(20),
+            Err(PruneError::IndexOutOfBounds {
+                requested: 20,
+                max: 10
+            })
+        );
+        assert_eq!(data.length(), 11);
+        assert_eq!(data.prune(10), Ok(()));
+        assert_eq!(data.prune(0), Err(PruneError::Empty));
+
+        assert_eq!(
+            PruneError::IndexOutOfBounds {
+                requested: 11,
+                max: 10
+            }
+            .to_string(),
+            "index 11 is past the maximum index 10"
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let mut data = ChunkedData::<u64>::default();
+        assert_eq!(data.validate(), Ok(()));
+
+        test_populate(&mut data);
+        assert_eq!(data.validate(), Ok(()));
+
+        let cases = [
+            (
+                ChunkedData::from_raw_parts(10, false, vec![(5, vec![1]), (5, vec![2])]),
+                InvariantError::UnorderedChunk { chunk: 1 },
+            ),
+            (
+                ChunkedData::from_raw_parts(10, false, vec![(5, vec![1]), (2, vec![2])]),
+                InvariantError::UnorderedChunk { chunk: 1 },
+            ),
+            (
+                ChunkedData::from_raw_parts(10, false, vec![(0, vec![1, 2, 3]), (2, vec![4])]),
+                InvariantError::OverlappingChunk { chunk: 1 },
+            ),
+            (
+                ChunkedData::from_raw_parts(10, false, vec![(0, vec![1]), (2, vec![])]),
+                InvariantError::EmptyChunk { chunk: 1 },
+            ),
+            (
+                ChunkedData::from_raw_parts(3, true, vec![(0, vec![1]), (2, vec![2, 3])]),
+                InvariantError::ChunkPastLength { chunk: 1 },
+            ),
+            (
+                ChunkedData::from_raw_parts(3, true, vec![]),
+                InvariantError::ActiveWithoutChunk,
+            ),
+            (
+                ChunkedData::from_raw_parts(4, true, vec![(0, vec![1]), (2, vec![2])]),
+                InvariantError::ActiveChunkNotAtEnd,
+            ),
+        ];
+
+        for (data, expected) in cases {
+            assert_eq!(data.validate(), Err(expected), "{data:?}");
+        }
+
+        assert_eq!(
+            InvariantError::OverlappingChunk { chunk: 1 }.to_string(),
+            "chunk 1 overlaps the previous chunk"
+        );
+    }
+
+    #[test]
+    fn get() {
+        let mut data = ChunkedData::<u64>::default();
+        assert_eq!(data.get(0), None);
+
+        test_populate(&mut data);
+
+        for (index, expected) in POPULATION.iter().enumerate() {
+            assert_eq!(data.get(index), expected.as_ref());
+            assert_eq!(data.contains_index(index), expected.is_some());
+        }
+
+        assert_eq!(data.get(POPULATION.len()), None);
+        assert!(!data.contains_index(POPULATION.len()));
+    }
+
+    #[test]
+    fn index_of_first_and_last() {
+        let mut data = ChunkedData::<u64>::default();
+        assert_eq!(data.index_of_first_at_or_after(0), None);
+        assert_eq!(data.index_of_last_at_or_before(0), None);
+
+        data.try_push(None);
+        test_populate(&mut data);
+        data.try_push(None);
+
+        // Stored indices are 1..=3 and 7..=10, with a length of 12.
+        let first_at_or_after = [
+            Some(1),
+            Some(1),
+            Some(2),
+            Some(3),
+            Some(7),
+            Some(7),
+            Some(7),
+            Some(7),
+            Some(8),
+            Some(9),
+            Some(10),
+            None,
+            None,
+        ];
+        let last_at_or_before = [
+            None,
+            Some(1),
+            Some(2),
+            Some(3),
+            Some(3),
+            Some(3),
+            Some(3),
+            Some(7),
+            Some(8),
+            Some(9),
+            Some(10),
+            Some(10),
+            Some(10),
+        ];
+
+        for index in 0..first_at_or_after.len() {
+            assert_eq!(
+                data.index_of_first_at_or_after(index),
+                first_at_or_after[index],
+                "first at or after {index}"
+            );
+            assert_eq!(
+                data.index_of_last_at_or_before(index),
+                last_at_or_before[index],
+                "last at or before {index}"
+            );
+        }
+
+        assert_eq!(data.index_of_first_at_or_after(usize::MAX), None);
+        assert_eq!(data.index_of_last_at_or_before(usize::MAX), Some(10));
+    }
+
+    #[test]
+    fn sample_at_indices() {
+        let mut data = ChunkedData::<u64>::default();
+        assert_eq!(data.sample_at_indices(&[0, 1]), Ok(vec![None, None]));
+
+        test_populate(&mut data);
+
+        let all = (0..=POPULATION.len()).collect::<Vec<_>>();
+        assert_eq!(
+            data.sample_at_indices(&all),
+            Ok(all.iter().map(|&index| data.get(index)).collect())
+        );
+
+        assert_eq!(
+            data.sample_at_indices(&[1, 1, 4, 9, 20]),
+            Ok(vec![Some(&2), Some(&2), None, Some(&10), None])
+        );
+        assert_eq!(data.sample_at_indices(&[]), Ok(vec![]));
+        assert_eq!(
+            data.sample_at_indices(&[1, 5, 3]),
+            Err(UnsortedIndicesError { position: 2 })
+        );
+    }
+
+    /// Builds a non-decreasing series with gaps, alongside what it looks like logically.
+    fn cumulative_series() -> (ChunkedData<u32>, Vec<Option<u32>>) {
+        let mut data = ChunkedData::default();
+        let mut logical = Vec::new();
+        let mut total = 0;
+
+        for i in 0..40u32 {
+            let item = if i % 7 == 3 || i % 11 == 0 {
+                None
+            } else {
+                // Some steps don't increase, so there are runs of equal values.
+                total += i % 3;
+                Some(total)
+            };
+
+            data.try_push(item);
+            logical.push(item);
+        }
+        data.try_push(None);
+        logical.push(None);
+
+        (data, logical)
+    }
+
+    #[test]
+    fn partition_point() {
+        let (data, logical) = cumulative_series();
+
+        for target in 0..=50 {
+            let expected = logical
+                .iter()
+                .position(|item| item.is_some_and(|item| item >= target));
+            assert_eq!(
+                data.partition_point(|&item| item < target),
+                expected,
+                "target {target}"
+            );
+        }
+
+        assert_eq!(
+            ChunkedData::<u64>::default().partition_point(|_| true),
+            None
+        );
+    }
+
+    #[test]
+    fn binary_search_by() {
+        let (data, logical) = cumulative_series();
+
+        for target in 0..=50 {
+            let result = data.binary_search_by(|item| item.cmp(&target));
+
+            match result {
+                Ok(index) => assert_eq!(logical[index], Some(target), "target {target}"),
+                Err(index) => {
+                    let expected = logical
+                        .iter()
+                        .position(|item| item.is_some_and(|item| item > target))
+                        .unwrap_or(data.length());
+
+                    assert!(!logical.contains(&Some(target)), "target {target}");
+                    assert_eq!(index, expected, "target {target}");
+                }
+            }
+        }
+
+        assert_eq!(
+            ChunkedData::<u64>::default().binary_search_by(|item| item.cmp(&1)),
+            Err(0)
+        );
+    }
+
+    #[test]
+    fn min_max_by_key() {
+        let mut data = ChunkedData::<u64>::default();
+        for p in [
+            Some(3),
+            None,
+            Some(7),
+            Some(1),
+            None,
+            Some(7),
+            Some(1),
+            Some(5),
+        ] {
+            data.try_push(p);
+        }
+
+        // Ties go to the last maximum and the first minimum.
+        assert_eq!(data.max_by_key(|v| *v), Some((5, &7)));
+        assert_eq!(data.min_by_key(|v| *v), Some((3, &1)));
+        assert_eq!(data.max_by_key(|v| v.abs_diff(4)), Some((6, &1)));
+        assert_eq!(data.min_by_key(|v| v.abs_diff(4)), Some((0, &3)));
+
+        let empty = ChunkedData::<u64>::default();
+        assert_eq!(empty.max_by_key(|v| *v), None);
+        assert_eq!(empty.min_by_key(|v| *v), None);
+    }
+
+    #[test]
+    fn quantiles() {
+        let (data, logical) = cumulative_series();
+        let mut sorted = logical.iter().flatten().copied().collect::<Vec<_>>();
+        sorted.sort_unstable();
+
+        let reference =
+            |sorted: &[u32], q: f64| sorted[(q * (sorted.len() - 1) as f64).round() as usize];
+
+        let qs = [0.0, 0.5, 0.95, 0.99, 0.25, 1.0, 0.5, 0.1];
+        assert_eq!(
+            data.quantiles(&qs),
+            Ok(qs.iter().map(|&q| reference(&sorted, q)).collect())
+        );
+        assert_eq!(data.quantile(0.0), Ok(sorted.first().copied()));
+        assert_eq!(data.quantile(1.0), Ok(sorted.last().copied()));
+        assert_eq!(data.quantile(0.5), Ok(Some(reference(&sorted, 0.5))));
+
+        // Within a range.
+        let range = 5..30;
+        let mut sorted_range = logical[range.clone()]
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        sorted_range.sort_unstable();
+        assert_eq!(
+            data.quantiles_in_range(range, &qs),
+            Ok(qs.iter().map(|&q| reference(&sorted_range, q)).collect())
+        );
+
+        // Empty ranges.
+        assert_eq!(data.quantiles_in_range(3..4, &qs), Ok(vec![]));
+        assert_eq!(data.quantiles_in_range(100..200, &qs), Ok(vec![]));
+        assert_eq!(ChunkedData::<u64>::default().quantile(0.5), Ok(None));
+        assert_eq!(data.quantiles(&[]), Ok(vec![]));
+
+        // Out of range quantiles.
+        assert_eq!(
+            data.quantile(1.5),
+            Err(InvalidQuantileError { position: 0 })
+        );
+        assert_eq!(
+            data.quantiles(&[0.5, -0.1]),
+            Err(InvalidQuantileError { position: 1 })
+        );
+        assert_eq!(
+            data.quantiles(&[0.5, 0.7, f64::NAN]),
+            Err(InvalidQuantileError { position: 2 })
+        );
+        assert_eq!(
+            ChunkedData::<u64>::default().quantile(2.0),
+            Err(InvalidQuantileError { position: 0 })
+        );
+    }
+
+    #[test]
+    fn moving_average() {
+        let (data, logical) = cumulative_series();
+
+        for window in [1, 2, 3, 5, 100] {
+            let averaged = data.moving_average(window);
+            assert!(averaged.validate().is_ok());
+            assert!(averaged.layout_mismatch(&data).is_none());
+
+            // Each chunk is averaged on its own.
+            let mut expected = Vec::new();
+            for run in logical.split(Option::is_none) {
+                for end in 1..=run.len() {
+                    let window = &run[end.saturating_sub(window)..end];
+                    let sum: u32 = window.iter().flatten().sum();
+                    expected.push(sum as f64 / window.len() as f64);
+                }
+            }
+
+            assert_eq!(averaged.iter().copied().collect::<Vec<_>>(), expected);
+        }
+
+        // A break without a gap also starts a fresh window. 64-bit integers work too.
+        let mut data = ChunkedData::<u64>::default();
+        data.extend([2, 4]);
+        data.insert_break();
+        data.extend([10, 20]);
+        assert_eq!(
+            data.moving_average(2).iter_with_index().collect::<Vec<_>>(),
+            vec![(0, &2.0), (1, &3.0), (2, &10.0), (3, &15.0)]
+        );
+
+        assert_eq!(
+            ChunkedData::<u32>::default().moving_average(3),
+            ChunkedData::<f64>::default()
+        );
+    }
+
+    #[test]
+    fn prefix_sum() {
+        let mut data = ChunkedData::<u64>::default();
+        assert_eq!(data.prefix_sum(), data);
+        assert_eq!(data.global_prefix_sum(), data);
+
+        data.try_push(None);
+        test_populate(&mut data);
+        data.insert_break();
+        data.push(100);
+        data.try_push(None);
+
+        let summed = data.prefix_sum();
+        assert!(summed.validate().is_ok());
+        assert!(summed.layout_mismatch(&data).is_none());
+        assert_eq!(
+            summed.iter_with_index().collect::<Vec<_>>(),
+            vec![
+                (1, &1),
+                (2, &3),
+                (3, &6),
+                (7, &7),
+                (8, &15),
+                (9, &24),
+                (10, &34),
+                (11, &100)
+            ]
+        );
+
+        let summed = data.global_prefix_sum();
+        assert!(summed.layout_mismatch(&data).is_none());
+        assert_eq!(
+            summed.iter_with_index().collect::<Vec<_>>(),
+            vec![
+                (1, &1),
+                (2, &3),
+                (3, &6),
+                (7, &13),
+                (8, &21),
+                (9, &30),
+                (10, &40),
+                (11, &140)
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "window must be greater than 0")]
+    fn rolling_window_zero() {
+        ChunkedData::<u32>::from(vec![1])
+            .rolling_window_iter(0)
+            .count();
+    }
+
+    #[test]
+    #[should_panic(expected = "window must be greater than 0")]
+    fn moving_average_zero() {
+        ChunkedData::<u32>::default().moving_average(0);
+    }
+
+    #[test]
+    fn non_chunked_conversion() {
+        // Empty.
+        let data = ChunkedData::<u64>::default();
+        assert!(data.is_contiguous());
+        let non_chunked = data.try_into_non_chunked().unwrap();
+        assert!(non_chunked.is_empty());
+        assert_eq!(
+            ChunkedData::from(non_chunked),
+            ChunkedData::<u64>::default()
+        );
+
+        // Single chunk.
+        let items = vec![1, 2, 3];
+        let ptr = items.as_ptr();
+        let mut data = ChunkedData::<u64>::default();
+        data.extend_from_vec(items);
+        data.insert_break();
+        assert!(data.is_contiguous());
+
+        let expected = data.clone();
+        let non_chunked = data.try_into_non_chunked().unwrap();
+        assert_eq!(non_chunked.as_slice(), &[1, 2, 3]);
+        assert_eq!(non_chunked.as_slice().as_ptr(), ptr);
+
+        let mut data = ChunkedData::from(non_chunked);
+        assert_eq!(data, expected);
+        assert_eq!(data.chunks[0].data.as_ptr(), ptr);
+
+        // Trailing gap.
+        data.try_push(None);
+        assert!(!data.is_contiguous());
+        assert_eq!(data.clone().try_into_non_chunked(), Err(data));
+
+        // Leading gap, and only gaps.
+        let mut data = ChunkedData::<u64>::default();
+        data.try_push(None);
+        assert!(!data.is_contiguous());
+        data.push(1);
+        assert!(!data.is_contiguous());
+
+        // Multiple chunks.
+        let mut data = ChunkedData::<u64>::default();
+        test_populate(&mut data);
+        assert!(!data.is_contiguous());
+        assert_eq!(data.clone().try_into_non_chunked(), Err(data));
+    }
+
+    #[test]
+    fn parts_round_trip() {
+        let mut data = ChunkedData::<u64>::default();
+        let (next_index, is_active, chunks) = data.clone().into_parts();
+        assert_eq!((next_index, is_active, chunks.len()), (0, false, 0));
+        assert_eq!(
+            ChunkedData::from_parts(0, false, Vec::new()),
+            Ok(data.clone())
+        );
+
+        for p in POPULATION {
+            data.try_push(p);
+        }
+        data.try_push(None);
+
+        let (next_index, is_active, chunks) = data.clone().into_parts();
+        assert_eq!((next_index, is_active), (11, false));
+        assert_eq!(chunks, vec![(0, vec![1, 2, 3]), (6, vec![7, 8, 9, 10])]);
+
+        let mut restored = ChunkedData::from_parts(next_index, is_active, chunks).unwrap();
+        assert_eq!(restored, data);
+
+        // The trailing gap means a new chunk should be started.
+        restored.push(12);
+        assert_eq!(restored.last_chunk_start_offset(), Some(11));
+
+        // An active chunk continues.
+        let (next_index, is_active, chunks) = restored.into_parts();
+        assert!(is_active);
+        let mut restored = ChunkedData::from_parts(next_index, is_active, chunks).unwrap();
+        restored.push(13);
+        assert_eq!(restored.latest_run(), Some((11, &[12, 13][..])));
+        assert_eq!(restored.num_elements(), 9);
+
+        // A sealed chunk doesn't, even without a gap after it.
+        let mut restored = ChunkedData::from_parts(3, false, vec![(1, vec![2, 3])]).unwrap();
+        restored.push(4);
+        assert_eq!(restored.latest_run(), Some((3, &[4][..])));
+    }
+
+    #[test]
+    fn from_parts_invalid() {
+        assert_eq!(
+            ChunkedData::from_parts(10, false, vec![(0, vec![1, 2, 3]), (2, vec![3])]),
+            Err(InvariantError::OverlappingChunk { chunk: 1 })
+        );
+        assert_eq!(
+            ChunkedData::from_parts(10, false, vec![(5, vec![1]), (0, vec![1])]),
+            Err(InvariantError::UnorderedChunk { chunk: 1 })
+        );
+        assert_eq!(
+            ChunkedData::from_parts(4, false, vec![(0, vec![1]), (2, vec![1, 2, 3])]),
+            Err(InvariantError::ChunkPastLength { chunk: 1 })
+        );
+        assert_eq!(
+            ChunkedData::from_parts(4, false, vec![(0, Vec::<u64>::new())]),
+            Err(InvariantError::EmptyChunk { chunk: 0 })
+        );
+        assert_eq!(
+            ChunkedData::<u64>::from_parts(4, true, vec![]),
+            Err(InvariantError::ActiveWithoutChunk)
+        );
+        assert_eq!(
+            ChunkedData::from_parts(4, true, vec![(0, vec![1, 2])]),
+            Err(InvariantError::ActiveChunkNotAtEnd)
+        );
+    }
+
+    #[test]
+    fn unzip_and_zip() {
+        let mut data = ChunkedData::<(u64, u64)>::default();
+        for (index, p) in POPULATION.into_iter().enumerate() {
+            data.try_push(p.map(|p| (p, p * 10)));
+            if index == 1 {
+                data.insert_break();
+            }
+        }
+        data.try_push(None);
+
+        let (left, right) = data.clone().unzip();
+        for half in [&left, &right] {
+            assert_eq!(half.length(), data.length());
+            assert_eq!(half.is_active, data.is_active);
+            assert_eq!(
+                half.chunks
+                    .iter()
+                    .map(|c| (c.start_offset, c.data.len()))
+                    .collect::<Vec<_>>(),
+                data.chunks
+                    .iter()
+                    .map(|c| (c.start_offset, c.data.len()))
+                    .collect::<Vec<_>>()
+            );
+        }
+        assert_eq!(
+            left.iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 7, 8, 9, 10]
+        );
+        assert_eq!(right.get(6), Some(&70));
+
+        let zipped = left.clone().zip_into(right.clone()).unwrap();
+        assert_eq!(zipped, data);
+        assert_eq!(zipped.length(), 11);
+
+        // The same gaps, but chunked differently, is fine.
+        let mut other = ChunkedData::<u64>::default();
+        test_populate(&mut other);
+        other.try_push(None);
+        assert_eq!(left.clone().zip_into(other.clone()).unwrap().length(), 11);
+
+        // Round trip through an empty one.
+        let (left, right) = ChunkedData::<(u64, u64)>::default().unzip();
+        assert_eq!(left.zip_into(right), Ok(ChunkedData::default()));
+    }
+
+    #[test]
+    fn zip_mismatched_layouts() {
+        let mut a = ChunkedData::<u64>::default();
+        test_populate(&mut a);
+
+        // A gap in a different place.
+        let mut b = ChunkedData::<u64>::default();
+        for (index, p) in POPULATION.into_iter().enumerate() {
+            b.try_push(if index == 7 { None } else { p });
+        }
+        assert_eq!(a.clone().zip_into(b), Err(LayoutMismatchError { index: 7 }));
+
+        // A different length.
+        let mut c = a.clone();
+        c.try_push(None);
+        assert_eq!(
+            a.clone().zip_into(c),
+            Err(LayoutMismatchError { index: 10 })
+        );
+
+        let mut d = ChunkedData::<u64>::default();
+        d.try_push(None);
+        assert_eq!(a.zip_into(d), Err(LayoutMismatchError { index: 0 }));
+    }
+
+    #[test]
+    fn index() {
+        let mut data = ChunkedData::<u64>::default();
+        test_populate(&mut data);
+
+        assert_eq!(data[0], 1);
+        assert_eq!(data[2], 3);
+        assert_eq!(data[6], 7);
+        assert_eq!(data[9], 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "index 4 is a gap")]
+    fn index_gap() {
+        let mut data = ChunkedData::<u64>::default();
+        test_populate(&mut data);
+
+        let _ = data[4];
+    }
+
+    #[test]
+    #[should_panic(expected = "index 10 out of range 0..10")]
+    fn index_out_of_range() {
+        let mut data = ChunkedData::<u64>::default();
+        test_populate(&mut data);
+
+        let _ = data[10];
+    }
+
+    #[test]
+    #[should_panic(expected = "rotation 11 out of range for length 10")]
+    fn rotate_out_of_range() {
+        let mut data = ChunkedData::<u64>::default();
+        test_populate(&mut data);
+
+        data.rotate_right(11);
+    }
+
+    #[track_caller]
+    fn assert_logical_eq(data: &ChunkedData<u64>, expected: &[Option<u64>]) {
+        assert_eq!(data.length(), expected.len());
+        assert_eq!(
+            (0..data.length())
+                .map(|i| data.get(i).copied())
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn reduce_by_factor() {
+        let mut data = ChunkedData::<u64>::default();
+        test_populate(&mut data);
+
+        assert_logical_eq(&data.reduce_by_factor(1), &POPULATION);
+        assert_logical_eq(
+            &data.reduce_by_factor(2),
+            &[Some(1), None, None, Some(7), Some(9)],
+        );
+        assert_logical_eq(
+            &data.reduce_by_factor(3),
+            &[Some(1), None, Some(7), Some(10)],
+        );
+        assert_logical_eq(&data.reduce_by_factor(10), &[None]);
+        assert_logical_eq(&data.reduce_by_factor(20), &[None]);
+
+        let empty = ChunkedData::<u64>::default();
+        assert_logical_eq(&empty.reduce_by_factor(2), &[]);
+    }
+
+    #[test]
+    fn reduce_by_factor_with() {
+        let mut data = ChunkedData::<u64>::default();
+        test_populate(&mut data);
+
+        let max = |group: &[u64]| *group.iter().max().unwrap();
+        assert_logical_eq(
+            &data.reduce_by_factor_with(2, max),
+            &[Some(2), None, None, Some(8), Some(10)],
+        );
+        assert_logical_eq(
+            &data.reduce_by_factor_with(4, |group| group.iter().sum()),
+            &[None, None, Some(19)],
+        );
+    }
+
+    /// Groups that span adjacent chunks without a gap between them are not gaps.
+    #[test]
+    fn reduce_by_factor_adjacent_chunks() {
+        let mut data = ChunkedData::<u64>::default();
+        data.push(1);
+        data.insert_break();
+        data.push(2);
+        data.push(3);
+        data.insert_break();
+        data.push(4);
+
+        assert_eq!(data.chunks.len(), 3);
+        assert_logical_eq(
+            &data.reduce_by_factor_with(4, |group| group.iter().sum()),
+            &[Some(10)],
+        );
+        assert_logical_eq(&data.reduce_by_factor(3), &[Some(1), Some(4)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "factor must be greater than 0")]
+    fn reduce_by_factor_zero() {
+        let data = ChunkedData::<u64>::default();
+        data.reduce_by_factor(0);
+    }
+
+    #[track_caller]
+    fn values_between_test<T: PartialOrd + Copy + std::fmt::Debug>(
+        data: &ChunkedData<u64>, base_slice: &[T], range: RangeInclusive<T>,
+    ) {
+        let result = data.values_between(base_slice, range.clone());
+        let expected = data
+            .iter_along_base(base_slice)
+            .filter(|(base, _)| range.contains(base))
+            .collect::<Vec<_>>();
+
+        assert_eq!(result.len(), expected.len());
+        assert_eq!(result.collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn values_between_f64() {
+        let mut data = ChunkedData::<u64>::default();
+        test_populate(&mut data);
+
+        let base_slice = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0];
+
+        assert_eq!(
+            data.values_between(&base_slice, 1.5..=7.0)
+                .map(|(a, b)| (*a, *b))
+                .collect::<Vec<_>>(),
+            vec![(2.0, 3), (6.0, 7), (7.0, 8)]
+        );
+
+        for (start, end) in [
+            (0.0, 9.0),
+            (-5.0, 20.0),
+            (-5.0, -1.0),
+            (10.0, 20.0),
+            (3.0, 5.0),
+            (3.5, 4.5),
+            (2.0, 6.0),
+            (5.0, 2.0),
+            (9.0, 9.0),
+        ] {
+            values_between_test(&data, &base_slice, start..=end);
+        }
+
+        // A shorter base slice only returns values it covers.
+        values_between_test(&data, &base_slice[..7], 0.0..=9.0);
+        values_between_test(&data, &base_slice[..2], 0.0..=9.0);
+        values_between_test(&data, &base_slice[..0], 0.0..=9.0);
+    }
+
+    #[test]
+    fn values_between_instant() {
+        use std::time::{Duration, Instant};
+
+        let mut data = ChunkedData::<u64>::default();
+        test_populate(&mut data);
+
+        let now = Instant::now();
+        let base_slice = (0..POPULATION.len() as u64)
+            .map(|i| now + Duration::from_secs(i))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            data.values_between(&base_slice, base_slice[5]..=base_slice[8])
+                .map(|(_, b)| *b)
+                .collect::<Vec<_>>(),
+            vec![7, 8, 9]
+        );
+
+        for (start, end) in [(0, 9), (0, 2), (3, 5), (2, 7), (9, 9)] {
+            values_between_test(&data, &base_slice, base_slice[start]..=base_slice[end]);
+        }
+
+        values_between_test(
+            &data,
+            &base_slice,
+            now + Duration::from_secs(20)..=now + Duration::from_secs(30),
+        );
+    }
 
-            for chunk in &mut self.chunks {
-                chunk.start_offset -= to_remove;
-            }
+    #[test]
+    fn rate_along_base() {
+        use std::time::Duration;
+
+        let now = Instant::now();
+        let millis = [0, 500, 1500, 2000, 2000, 3000, 2500, 4000, 6000, 7000];
+        let base = millis
+            .iter()
+            .map(|&ms| now + Duration::from_millis(ms))
+            .collect::<Vec<_>>();
+
+        // A cumulative counter, like the number of bytes received.
+        let mut data = ChunkedData::<u64>::default();
+        for counter in [0, 100, 300, 400] {
+            data.push(counter);
         }
+        // The time doesn't change for the first, and goes backwards for the last.
+        data.push(500);
+        data.push(600);
+        data.push(700);
+        data.try_push(None);
+        data.push(1000);
+        // A counter reset.
+        data.push(0);
+        // Past the end of the base slice.
+        data.push(10);
 
-        Ok(())
+        assert_eq!(
+            data.rate_along_base(&base).collect::<Vec<_>>(),
+            vec![(1, 200.0), (2, 200.0), (3, 200.0), (5, 100.0), (9, -1000.0)]
+        );
+        assert_eq!(
+            data.rate_along_base(&base)
+                .rev()
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>(),
+            vec![9, 5, 3, 2, 1]
+        );
+
+        // A single element has nothing to compare with.
+        let mut data = ChunkedData::<u64>::default();
+        data.push(1);
+        assert_eq!(data.rate_along_base(&base).next(), None);
     }
 
-    /// Shrink the [`ChunkedData`] after.
-    pub fn shrink_to_fit(&mut self) {
-        for chunk in &mut self.chunks {
-            chunk.data.shrink_to_fit();
+    /// A naive implementation of [`ChunkedData::min_max_in_range`] to compare against.
+    fn naive_min_max(data: &ChunkedData<f64>, range: Range<usize>) -> Option<(f64, f64)> {
+        data.iter_with_index()
+            .filter(|(index, value)| range.contains(index) && !value.is_nan())
+            .fold(None, |acc, (_, &value)| match acc {
+                None => Some((value, value)),
+                Some((min, max)) => Some((f64::min(min, value), f64::max(max, value))),
+            })
+    }
+
+    #[test]
+    fn min_max_in_range() {
+        let mut data = ChunkedData::<f64>::default();
+        for i in 0..100 {
+            if i % 13 == 5 || (40..50).contains(&i) {
+                data.try_push(None);
+            } else if i % 7 == 0 {
+                data.push(f64::NAN);
+            } else {
+                data.push(((i * 37) % 101) as f64 - 50.0);
+            }
         }
 
-        self.chunks.shrink_to_fit();
+        for start in (0..=100).step_by(3) {
+            for end in (start..=105).step_by(7) {
+                assert_eq!(
+                    data.min_max_in_range(start..end),
+                    naive_min_max(&data, start..end),
+                    "the min and max for {start}..{end} should match"
+                );
+            }
+        }
+
+        assert_eq!(data.min_max_in_range(40..50), None);
+        assert_eq!(data.min_max_in_range(200..300), None);
     }
 
-    /// Convenience function to prune _and_ shrink the [`ChunkedData`] after.
-    pub fn prune_and_shrink_to_fit(&mut self, index: usize) -> Result<(), usize> {
-        self.prune(index)?;
-        self.shrink_to_fit();
+    #[test]
+    fn min_max_in_range_nan() {
+        let mut data = ChunkedData::<f32>::default();
+        assert_eq!(data.min_max_in_range(0..10), None);
 
-        Ok(())
-    }
+        data.push(f32::NAN);
+        data.push(f32::NAN);
+        assert_eq!(data.min_max_in_range(0..10), None);
 
-    /// Try and return the first element.
-    pub fn first(&self) -> Option<&D> {
-        self.chunks.first().and_then(|chunk| chunk.data.first())
-    }
+        data.push(1.5);
+        data.try_push(None);
+        data.push(-2.0);
+        data.push(f32::NAN);
+        assert_eq!(data.min_max_in_range(0..10), Some((-2.0, 1.5)));
+        assert_eq!(data.min_max_in_range(0..3), Some((1.5, 1.5)));
 
-    /// Try and return the last element.
-    pub fn last(&self) -> Option<&D> {
-        self.chunks.last().and_then(|chunk| chunk.data.last())
+        data.push(f32::INFINITY);
+        assert_eq!(data.min_max_in_range(0..10), Some((-2.0, f64::INFINITY)));
     }
 
-    /// Return whether there are zero elements left stored internally.
-    pub fn no_elements(&self) -> bool {
-        self.num_elements() == 0
-    }
-}
+    #[test]
+    fn try_push_finite() {
+        let values = [
+            1.0,
+            f64::NAN,
+            2.5,
+            -3.0,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            0.0,
+            f64::NAN,
+        ];
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let mut data = ChunkedData::<f64>::default();
+        let mut expected = ChunkedData::<f64>::default();
 
-    #[test]
-    fn chunked_push() {
-        let mut data = ChunkedData::default();
-        assert!(data.no_elements());
+        for value in values {
+            let outcome = data.try_push_finite(value);
+
+            if value.is_finite() {
+                assert_eq!(outcome, PushOutcome::Pushed);
+                expected.try_push(Some(value));
+            } else {
+                assert_eq!(outcome, PushOutcome::Gap);
+                expected.try_push(None);
+            }
+        }
+
+        assert_eq!(data, expected);
+        assert_eq!(data.length(), values.len());
+        assert_eq!(data.chunks.len(), 3);
+        assert!(!data.is_active);
+        assert_eq!(data.min_max_in_range(0..values.len()), Some((-3.0, 2.5)));
 
-        data.try_push(Some(1));
+        let mut data = ChunkedData::<f32>::default();
+        assert_eq!(data.try_push_finite(f32::NAN), PushOutcome::Gap);
+        assert_eq!(data.try_push_finite(1.0), PushOutcome::Pushed);
+        assert_eq!(data.iter_with_index().collect::<Vec<_>>(), vec![(1, &1.0)]);
+    }
 
-        assert!(!data.chunks.is_empty());
-        assert!(data.is_active);
-        assert_eq!(data.chunks.last().as_ref().unwrap().data, vec![1]);
-        assert_eq!(data.next_index, 1);
+    #[test]
+    fn io_write() {
+        use std::io::Write;
 
-        data.try_push(Some(2));
+        let mut data = ChunkedData::<u8>::default();
+        write!(data, "hello").unwrap();
         data.try_push(None);
 
-        assert!(!data.is_active);
-        assert_eq!(data.chunks.len(), 1);
-        assert_eq!(data.chunks.first().unwrap().data, vec![1, 2]);
-        assert_eq!(data.next_index, 3);
+        let writer: &mut dyn Write = &mut data;
+        writer.write_all(b" world").unwrap();
+        writer.write_all(b"").unwrap();
+        writer.flush().unwrap();
+
+        assert_eq!(data.length(), 12);
+        assert_eq!(data.chunks.len(), 2);
+        assert_eq!(data.chunks[0].data, b"hello");
+        assert_eq!(data.latest_run(), Some((6, &b" world"[..])));
+    }
+
+    #[test]
+    fn stats() {
+        let mut data = ChunkedData::<u64>::default();
+        assert_eq!(data.stats(), ChunkedStats::default());
+
+        test_populate(&mut data);
+        assert_eq!(
+            data.stats(),
+            ChunkedStats {
+                num_chunks: 2,
+                num_elements: 7,
+                num_skipped: 3,
+                average_chunk_length: 3.5,
+                max_chunk_length: 4,
+                num_gaps: 1,
+                average_gap_length: 3.0,
+                max_gap_length: 3,
+                is_active: true,
+            }
+        );
 
+        // A break without a gap adds a chunk, but not a gap.
+        let mut data = ChunkedData::<u64>::default();
+        data.try_push(None);
+        data.push(1);
+        data.insert_break();
+        data.push(2);
+        data.push(3);
+        data.try_push(None);
+        data.try_push(None);
+        data.push(6);
         data.try_push(None);
-        assert!(!data.is_active);
-        assert_eq!(data.next_index, 4);
 
-        data.try_push(Some(3));
-        assert!(data.is_active);
-        assert_eq!(data.chunks.last().as_ref().unwrap().data, vec![3]);
-        assert_eq!(data.next_index, 5);
+        let stats = data.stats();
+        assert_eq!(
+            stats,
+            ChunkedStats {
+                num_chunks: 3,
+                num_elements: 4,
+                num_skipped: 4,
+                average_chunk_length: 4.0 / 3.0,
+                max_chunk_length: 2,
+                num_gaps: 3,
+                average_gap_length: 4.0 / 3.0,
+                max_gap_length: 2,
+                is_active: false,
+            }
+        );
+        assert_eq!(
+            stats.to_string(),
+            "chunks: 3 (avg length 1.33, max 2), elements: 4, gaps: 3 (avg length 1.33, max 2), skipped: 4, active: false"
+        );
 
-        assert_eq!(data.length(), 5);
-        assert_eq!(data.num_elements(), 3);
+        // Only gaps.
+        let mut data = ChunkedData::<u64>::default();
+        data.try_push(None);
+        data.try_push(None);
+        assert_eq!(data.stats().num_gaps, 1);
+        assert_eq!(data.stats().num_skipped, 2);
+        assert_eq!(data.stats().average_chunk_length, 0.0);
     }
 
-    /// Ensure that if we push nothing at first, we don't incorrectly try and
-    /// seal nothing.
     #[test]
-    fn chunked_empty_initial_push() {
-        let mut data: ChunkedData<u64> = ChunkedData::default();
+    fn display() {
+        let mut data = ChunkedData::<u64>::default();
+        assert_eq!(data.to_string(), "(length: 0, elements: 0)");
+
+        test_populate(&mut data);
+        assert_eq!(
+            data.to_string(),
+            "[1, 2, 3] ... [7, 8, 9, 10] (length: 10, elements: 7)"
+        );
 
         data.try_push(None);
-        assert!(!data.is_active);
+        assert_eq!(
+            data.to_string(),
+            "[1, 2, 3] ... [7, 8, 9, 10] ... (length: 11, elements: 7)"
+        );
 
-        data.try_push(Some(1));
-        assert!(data.is_active);
-        assert_eq!(data.next_index, 2);
+        let mut data = ChunkedData::<u64>::default();
+        data.try_push(None);
+        data.push(1);
+        data.insert_break();
+        data.push(2);
+        assert_eq!(data.to_string(), "... [1] [2] (length: 3, elements: 2)");
     }
 
-    const POPULATION: [Option<u64>; 10] = [
-        Some(1),
-        Some(2),
-        Some(3),
-        None,
-        None,
-        None,
-        Some(7),
-        Some(8),
-        Some(9),
-        Some(10),
-    ];
+    #[test]
+    fn debug() {
+        let mut data = ChunkedData::<u64>::default();
+        assert_eq!(
+            format!("{data:?}"),
+            "ChunkedData { length: 0, chunks: 0, stored: 0 }"
+        );
+        assert_eq!(
+            format!("{data:#?}"),
+            "ChunkedData {\n    length: 0,\n    stored: 0,\n    skipped: 0,\n    chunks: [],\n}"
+        );
 
-    fn test_populate(data: &mut ChunkedData<u64>) {
-        for p in POPULATION {
-            data.try_push(p);
-        }
+        data.try_push(None);
+        test_populate(&mut data);
+        data.try_push(None);
+        assert_eq!(
+            format!("{data:?}"),
+            "ChunkedData { length: 12, chunks: 2, stored: 7 }"
+        );
+        assert_eq!(
+            format!("{data:#?}"),
+            [
+                "ChunkedData {",
+                "    length: 12,",
+                "    stored: 7,",
+                "    skipped: 5,",
+                "    chunks: [",
+                "        [1..=3] 3 values (first=1, last=3),",
+                "        [7..=10] 4 values (first=7, last=10),",
+                "    ],",
+                "}",
+            ]
+            .join("\n")
+        );
+
+        // Nested alternate formatting should still be indented.
+        assert_eq!(
+            format!("{:#?}", Some(ChunkedData::<u64>::from(vec![5]))),
+            [
+                "Some(",
+                "    ChunkedData {",
+                "        length: 1,",
+                "        stored: 1,",
+                "        skipped: 0,",
+                "        chunks: [",
+                "            [0..=0] 1 value (first=5, last=5),",
+                "        ],",
+                "    },",
+                ")",
+            ]
+            .join("\n")
+        );
     }
 
-    /// Initialize data, prune, and insert.
-    #[track_caller]
-    fn test_pruning(to_prune_index: usize) {
-        // println!("Trying to prune up to index {to_prune_index}...");
+    #[test]
+    fn iter_indexed() {
+        let mut data = ChunkedData::<u64>::default();
+        test_populate(&mut data);
 
-        let mut data = ChunkedData::default();
+        assert!(data.iter_indexed().eq(data.iter_with_index()));
+
+        // Adapters that keep an exact size should still report it.
+        let iter = data.iter_indexed();
+        assert_eq!(iter.len(), 7);
+        assert_eq!(iter.rev().skip(2).len(), 5);
+        assert_eq!(data.iter_indexed().skip(1).take(3).len(), 3);
+        assert_eq!(
+            data.iter_indexed()
+                .rev()
+                .skip(2)
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>(),
+            vec![7, 6, 2, 1, 0]
+        );
+    }
+
+    #[test]
+    fn size_hint_after_consuming() {
+        let mut data: ChunkedData<u64> = ChunkedData::default();
         test_populate(&mut data);
 
-        assert!(data.prune(to_prune_index).is_ok());
+        let mut iter = data.iter_with_index();
+        assert_eq!(iter.size_hint(), (7, Some(7)));
 
-        let removed = to_prune_index + 1;
-        let result = data
-            .iter_with_index()
-            .map(|(a, b)| (a, *b))
-            .collect::<Vec<_>>();
+        iter.next();
+        iter.next();
+        assert_eq!(iter.size_hint(), (5, Some(5)));
+        assert_eq!(iter.len(), 5);
+
+        iter.next_back();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.by_ref().count(), 4);
+        assert_eq!(iter.size_hint(), (0, Some(0)));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.len(), 0);
+
+        let mut gaps = data.iter_with_gaps();
+        gaps.nth(3);
+        assert_eq!(gaps.len(), 6);
+    }
+
+    #[test]
+    fn iter_with_gaps() {
+        let mut data = ChunkedData::<u64>::default();
+        assert_eq!(data.iter_with_gaps().next(), None);
+
+        data.try_push(None);
+        data.try_push(None);
+        test_populate(&mut data);
+        data.try_push(None);
 
-        let expected = POPULATION
+        let expected = [None, None]
             .into_iter()
-            .skip(removed)
-            .enumerate()
-            .filter_map(|(a, b)| b.map(|b| (a, b)))
+            .chain(POPULATION)
+            .chain([None])
             .collect::<Vec<_>>();
 
-        assert_eq!(result, expected);
-        assert_eq!(data.next_index, POPULATION.len() - (to_prune_index + 1));
+        let iter = data.iter_with_gaps();
+        assert_eq!(iter.len(), expected.len());
+        assert_eq!(iter.map(|v| v.copied()).collect::<Vec<_>>(), expected);
+        assert_eq!(
+            data.iter_with_gaps()
+                .rev()
+                .map(|v| v.copied())
+                .collect::<Vec<_>>(),
+            expected.into_iter().rev().collect::<Vec<_>>()
+        );
     }
 
     #[test]
-    fn chunked_prune() {
-        test_pruning(0);
-        test_pruning(1);
-        test_pruning(2);
-        test_pruning(3);
-        test_pruning(4);
-        test_pruning(5);
-        test_pruning(6);
-        test_pruning(7);
-        test_pruning(8);
-        test_pruning(9);
+    fn logical_eq() {
+        let mut a = ChunkedData::<u64>::default();
+        let mut b = ChunkedData::<u64>::default();
+        assert_eq!(a, b);
+
+        test_populate(&mut a);
+        test_populate(&mut b);
+        assert_eq!(a, b);
+
+        // A break without a gap changes the layout, but not the logical contents.
+        let mut c = ChunkedData::<u64>::default();
+        for (index, p) in POPULATION.into_iter().enumerate() {
+            c.try_push(p);
+            if index == 1 || index == 7 {
+                c.insert_break();
+            }
+        }
+        assert_ne!(a.chunks.len(), c.chunks.len());
+        assert_eq!(a, c);
+
+        // Different gap positions.
+        let mut d = ChunkedData::<u64>::default();
+        for p in POPULATION.into_iter().rev() {
+            d.try_push(p);
+        }
+        assert_ne!(a, d);
+
+        // Different lengths due to a trailing gap.
+        b.try_push(None);
+        assert_ne!(a, b);
+        a.try_push(None);
+        assert_eq!(a, b);
+
+        // Different values.
+        a.push(1);
+        b.push(2);
+        assert_ne!(a, b);
     }
 
-    /// Handle if we try and prune something empty.
     #[test]
-    fn chunked_prune_empty() {
-        let mut data: ChunkedData<u64> = ChunkedData::default();
-        assert!(data.prune(0).is_err());
+    fn logical_hash() {
+        use std::collections::{HashMap, hash_map::DefaultHasher};
+
+        fn hash(data: &ChunkedData<u64>) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            data.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let mut a = ChunkedData::<u64>::default();
+        test_populate(&mut a);
+
+        let mut b = ChunkedData::<u64>::default();
+        for (index, p) in POPULATION.into_iter().enumerate() {
+            b.try_push(p);
+            if index == 1 {
+                b.insert_break();
+            }
+        }
+
+        assert_ne!(a.chunks.len(), b.chunks.len());
+        assert_eq!(a, b);
+        assert_eq!(hash(&a), hash(&b));
+
+        let mut map = HashMap::new();
+        map.insert(a.clone(), "a");
+        assert_eq!(map.get(&b), Some(&"a"));
+
+        // Moving a gap should change the hash.
+        let mut c = ChunkedData::<u64>::default();
+        c.try_push(None);
+        c.push(1);
+        let mut d = ChunkedData::<u64>::default();
+        d.push(1);
+        d.try_push(None);
+        assert_ne!(hash(&c), hash(&d));
+        assert_eq!(map.get(&c), None);
     }
 
-    /// Handle if we try to clear an index past the index stored.
+    /// Extending from a slice should only need to reserve once.
     #[test]
-    fn chunked_prune_past_index() {
-        let mut data = ChunkedData::default();
-        test_populate(&mut data);
+    fn extend_from_slice_reserves_once() {
+        let items = (0..1000).collect::<Vec<u64>>();
+
+        let mut data = ChunkedData::<u64>::default();
+        data.push(0);
+        data.extend_from_slice(&items);
 
-        assert!(data.prune(10).is_err());
+        let mut expected = vec![0u64];
+        expected.reserve(items.len());
+
+        assert_eq!(data.chunks[0].data.capacity(), expected.capacity());
     }
 
+    /// Extending from a [`Vec`] with no active chunk should reuse the allocation.
     #[test]
-    fn chunked_prune_without_curr() {
-        let mut data = ChunkedData::default();
-        test_populate(&mut data);
+    fn extend_from_vec_no_copy() {
+        let items = (0..1000).collect::<Vec<u64>>();
+        let ptr = items.as_ptr();
+
+        let mut data = ChunkedData::<u64>::default();
         data.try_push(None);
+        data.extend_from_vec(items);
 
-        assert!(data.prune(10).is_ok());
+        assert_eq!(data.chunks[0].data.as_ptr(), ptr);
+        assert_eq!(data.chunks[0].start_offset, 1);
+        assert_eq!(data.length(), 1001);
     }
 
+    /// Creating from a [`Vec`] should reuse the allocation rather than pushing each element.
     #[test]
-    fn prune_zero_when_none() {
-        let mut data = ChunkedData::default();
-        data.try_push(None);
-        data.try_push(None);
-        data.try_push(None);
-        test_populate(&mut data);
+    fn from_vec_no_copy() {
+        let mut items = Vec::with_capacity(1000);
+        items.extend(0..500u64);
+        let ptr = items.as_ptr();
 
-        assert!(data.prune(0).is_ok());
-        assert_eq!(data.chunks[0].start_offset, 2);
-        assert_eq!(data.chunks[1].start_offset, 8);
-        assert_eq!(data.next_index, POPULATION.len() + 3 - 1);
+        let data = ChunkedData::<u64>::from(items.clone());
+        assert!(data.iter().eq(items.iter()));
 
-        assert!(data.prune(0).is_ok());
-        assert_eq!(data.chunks[0].start_offset, 1);
-        assert_eq!(data.chunks[1].start_offset, 7);
-        assert_eq!(data.next_index, POPULATION.len() + 3 - 2);
+        let data = ChunkedData::<u64>::from(items);
+        assert_eq!(data.chunks[0].data.as_ptr(), ptr);
+        assert_eq!(data.chunks[0].data.capacity(), 1000);
+        assert_eq!(data.length(), 500);
+
+        let data = ChunkedData::<u64>::from(&data.chunks[0].data[..]);
+        assert_eq!(data.chunks[0].data.capacity(), 500);
+    }
+
+    #[test]
+    fn reserve() {
+        let mut data = ChunkedData::<u64>::default();
+
+        // With no active chunk, the next chunk should use the reserved space.
+        data.reserve_exact(100);
+        assert_eq!(data.recycled.len(), 1);
+        data.push(1);
+        assert_eq!(data.chunks[0].data.capacity(), 100);
+
+        let ptr = data.chunks[0].data.as_ptr();
+        for i in 1..100 {
+            data.push(i);
+        }
+        assert_eq!(data.chunks[0].data.as_ptr(), ptr);
 
-        assert!(data.prune(0).is_ok());
-        assert_eq!(data.chunks[0].start_offset, 0);
-        assert_eq!(data.chunks[1].start_offset, 6);
-        assert_eq!(data.next_index, POPULATION.len() + 3 - 3);
+        // With an active chunk, it should reserve space in that chunk.
+        data.reserve(50);
+        assert!(data.chunks[0].data.capacity() >= 150);
+        assert!(data.recycled.is_empty());
 
-        assert!(data.prune(0).is_ok());
-        assert_eq!(data.chunks[0].start_offset, 0);
-        assert_eq!(data.chunks[0].data.as_slice(), &[2, 3]);
-        assert_eq!(data.chunks[1].start_offset, 5);
-        assert_eq!(data.next_index, POPULATION.len() + 3 - 4);
+        data.try_push(None);
+        data.reserve(10);
+        data.reserve(20);
+        assert_eq!(data.recycled.len(), 1);
+        data.push(2);
+        assert!(data.chunks[1].data.capacity() >= 20);
+        assert_eq!(data.length(), 102);
     }
 
     #[test]
-    fn first_last() {
-        let mut data = ChunkedData::default();
-        test_populate(&mut data);
+    fn capacity() {
+        let mut data = ChunkedData::<u64>::default();
+        assert_eq!(data.capacity(), 0);
+        assert_eq!(data.chunk_capacity(), 0);
+
+        // Reserved space for a chunk that hasn't started yet isn't counted.
+        data.reserve_exact(10);
+        assert_eq!(data.capacity(), 0);
+
+        data.push(1);
+        assert_eq!(data.capacity(), 10);
+        assert!(data.chunk_capacity() >= 1);
+
+        data.try_push(None);
+        data.extend_from_vec({
+            let mut items = Vec::with_capacity(20);
+            items.push(2);
+            items
+        });
+        assert_eq!(data.capacity(), 30);
+        assert!(data.capacity() >= data.num_elements());
+        assert!(data.chunk_capacity() >= 2);
 
-        assert_eq!(data.first(), Some(&1));
-        assert_eq!(data.last(), Some(&10));
+        data.shrink_to_fit();
+        assert_eq!(data.capacity(), data.num_elements());
+        assert_eq!(data.chunk_capacity(), 2);
     }
 
     #[test]
-    fn iter() {
-        let mut data = ChunkedData::default();
-        test_populate(&mut data);
+    fn byte_size() {
+        let mut data = ChunkedData::<u64>::default();
+        assert_eq!(data.byte_size(), 0);
 
+        data.extend_from_vec(vec![1, 2, 3]);
+        data.try_push(None);
+        data.extend_from_vec(vec![5, 6]);
+        data.shrink_to_fit();
         assert_eq!(
-            data.into_owned_iter().collect::<Vec<_>>(),
-            POPULATION.iter().filter_map(|v| *v).collect::<Vec<_>>(),
+            data.byte_size(),
+            2 * size_of::<DataChunk<Vec<u64>>>() + 5 * size_of::<u64>()
         );
+
+        // Recycled buffers still hold onto their allocations.
+        data.prune_all();
+        assert_eq!(data.chunk_capacity(), 2);
+        assert!(data.byte_size() >= 2 * size_of::<DataChunk<Vec<u64>>>() + 5 * size_of::<u64>());
+        data.clear_recycled_buffers();
+        assert_eq!(data.byte_size(), 2 * size_of::<DataChunk<Vec<u64>>>());
     }
 
+    #[cfg(feature = "smallvec")]
     #[test]
-    fn reverse_iter() {
-        let mut data = ChunkedData::default();
-        test_populate(&mut data);
+    fn byte_size_inline() {
+        let mut data = ChunkedData::<u64, smallvec::SmallVec<[u64; 4]>>::default();
+        data.extend_from_slice(&[1, 2, 3]);
+        data.shrink_to_fit();
 
-        assert_eq!(
-            data.into_owned_iter().rev().collect::<Vec<_>>(),
-            POPULATION
-                .iter()
-                .filter_map(|v| *v)
-                .rev()
-                .collect::<Vec<_>>(),
-        );
+        // Inline elements are already counted within the chunk itself.
+        let chunk_size = size_of::<DataChunk<smallvec::SmallVec<[u64; 4]>>>();
+        assert_eq!(data.byte_size(), chunk_size);
+
+        data.extend_from_slice(&[4, 5]);
+        data.shrink_to_fit();
+        assert_eq!(data.byte_size(), chunk_size + 5 * size_of::<u64>());
     }
 
-    #[track_caller]
-    fn base_slice_test(
-        data: &ChunkedData<u32>, base_slice: &[u32], expected: &[(u32, u32)],
-        base_slice_index: usize, expected_slice_index: usize,
-    ) {
-        let base_slice = &base_slice[0..base_slice_index];
+    /// Pruning times separately and rebasing should keep the data lined up with them.
+    #[test]
+    fn rebase_after_time_prune() {
+        use std::time::{Duration, Instant, UNIX_EPOCH};
 
+        use crate::time::OffsetTimeList;
+
+        let mut times = OffsetTimeList::default();
+        let mut data = ChunkedData::<u64>::default();
+
+        let now = Instant::now();
+        for i in 0..6 {
+            times.add(now + Duration::from_secs(i));
+            times.checkpoint();
+            data.try_push((i >= 3).then_some(i));
+        }
+
+        let removed = times.prune(Duration::from_secs(3)).unwrap().removed;
+        assert_eq!(removed, 2);
+        assert_eq!(data.rebase(removed), Ok(()));
+        assert!(data.validate().is_ok());
+        assert_eq!(data.length(), 4);
+
+        let base = times.to_unix_millis(UNIX_EPOCH + Duration::from_secs(5));
+        assert_eq!(base, vec![2000, 3000, 4000, 5000]);
         assert_eq!(
-            data.iter_along_base(base_slice).len(),
-            expected_slice_index,
-            "the returned size of the iterator should match"
+            data.iter_along_base(&base).collect::<Vec<_>>(),
+            vec![(&3000, &3), (&4000, &4), (&5000, &5)]
         );
+
+        // The next removal would drop stored elements.
+        assert_eq!(data.rebase(2), Err(RebaseError::StoredElement { index: 1 }));
         assert_eq!(
-            data.iter_along_base(base_slice)
-                .map(|(a, b)| (*a, *b))
-                .collect::<Vec<_>>(),
-            expected[0..expected_slice_index],
-            "the actual generated values should match"
+            data.rebase(5),
+            Err(RebaseError::PastLength {
+                removed: 5,
+                length: 4
+            })
         );
+        assert_eq!(data.length(), 4);
+        assert_eq!(data.rebase(1), Ok(()));
+        assert_eq!(data.rebase(0), Ok(()));
+        assert_eq!(data.first(), Some(&3));
+        assert_eq!(data.get(0), Some(&3));
     }
 
     #[test]
-    fn base_slice_simple() {
-        let mut data = ChunkedData::default();
+    fn prune_recycles_buffers() {
+        let mut data = ChunkedData::<u64>::default();
+        for i in 0..100 {
+            data.push(i);
+        }
+        data.try_push(None);
+        data.push(100);
+
+        let ptr = data.chunks[0].data.as_ptr();
+        let capacity = data.chunks[0].data.capacity();
+
+        assert!(data.prune(100).is_ok());
+        assert_eq!(data.recycled.len(), 1);
+
+        // The next new chunk should reuse the pruned chunk's buffer.
+        data.insert_break();
+        data.push(101);
+        assert!(data.recycled.is_empty());
+        assert_eq!(data.chunks[1].data.as_ptr(), ptr);
+        assert_eq!(data.chunks[1].data.capacity(), capacity);
+        assert_eq!(
+            data.iter_with_index().collect::<Vec<_>>(),
+            vec![(0, &100), (1, &101)]
+        );
+    }
+
+    #[test]
+    fn recycled_buffers_are_bounded() {
+        let mut data = ChunkedData::<u64>::default();
+        for i in 0..(MAX_RECYCLED_BUFFERS as u64 * 2) {
+            data.push(i);
+            data.try_push(None);
+        }
+
+        data.prune_all();
+        assert_eq!(data.recycled.len(), MAX_RECYCLED_BUFFERS);
+        assert!(data.recycled.iter().all(|buffer| buffer.is_empty()));
+
+        data.clear_recycled_buffers();
+        assert!(data.recycled.is_empty());
+
         data.push(1);
+        data.try_push(None);
         data.push(2);
-        data.push(3);
+        data.drain(0..2).for_each(drop);
+        assert_eq!(data.recycled.len(), 1);
+
+        data.shrink_to_fit();
+        assert!(data.recycled.is_empty());
+        assert_eq!(data.iter_with_index().collect::<Vec<_>>(), vec![(0, &2)]);
+    }
 
-        let base_slice = [1, 2, 3, 4, 5];
-        let expected = [(1, 1), (2, 2), (3, 3)];
+    #[test]
+    #[should_panic(expected = "index 0 out of range 0..0")]
+    fn index_empty() {
+        let data = ChunkedData::<u64>::default();
 
-        // Test with a larger base slice
-        base_slice_test(&data, &base_slice, &expected, 5, 3);
+        let _ = data[0];
+    }
 
-        // Test with an exact base slice
-        base_slice_test(&data, &base_slice, &expected, 3, 3);
+    #[test]
+    #[should_panic(expected = "index 1 is a gap")]
+    fn index_leading_gap() {
+        let mut data = ChunkedData::<u64>::default();
+        data.try_push(None);
+        data.try_push(None);
+        data.push(1);
 
-        // Test with a smaller base slice
-        base_slice_test(&data, &base_slice, &expected, 2, 2);
-        base_slice_test(&data, &base_slice, &expected, 1, 1);
-        base_slice_test(&data, &base_slice, &expected, 0, 0);
+        assert_eq!(data[2], 1);
+        let _ = data[1];
     }
 
     #[test]
-    fn base_slice_chunked() {
-        let mut data = ChunkedData::default();
+    #[should_panic(expected = "index 3 is a gap")]
+    fn index_trailing_gap() {
+        let mut data = ChunkedData::<u64>::default();
         data.push(1);
         data.push(2);
         data.push(3);
         data.try_push(None);
-        data.try_push(None);
-        data.try_push(None);
-        data.push(7);
-        data.push(8);
-        data.push(9);
 
-        let base_slice = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
-        let expected = [(1, 1), (2, 2), (3, 3), (7, 7), (8, 8), (9, 9)];
-
-        // Test with a larger base slice
-        base_slice_test(&data, &base_slice, &expected, 10, 6);
-
-        // Test with an exact base slice
-        base_slice_test(&data, &base_slice, &expected, 9, 6);
-
-        // Test with a smaller base slice
-        base_slice_test(&data, &base_slice, &expected, 8, 5);
-        base_slice_test(&data, &base_slice, &expected, 7, 4);
-        base_slice_test(&data, &base_slice, &expected, 6, 3);
-        base_slice_test(&data, &base_slice, &expected, 5, 3);
-        base_slice_test(&data, &base_slice, &expected, 4, 3);
-        base_slice_test(&data, &base_slice, &expected, 3, 3);
-        base_slice_test(&data, &base_slice, &expected, 2, 2);
-        base_slice_test(&data, &base_slice, &expected, 1, 1);
-        base_slice_test(&data, &base_slice, &expected, 0, 0);
+        assert_eq!(data[2], 3);
+        let _ = data[3];
     }
+
+    #[cfg(feature = "smallvec")]
+    storage_tests!(smallvec_storage, smallvec::SmallVec<[u64; 4]>);
 }
@@ -0,0 +1,216 @@
+//! A cursor for incrementally reading new elements from a [`ChunkedData`].
+
+use std::fmt;
+
+use super::{ChunkedData, ChunkedDataIter};
+use crate::data::ChunkStorage;
+
+/// An error returned by [`ChunkedCursor::next_new`] when entries that had not been
+/// read yet were pruned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CursorError {
+    /// Some unread logical entries (including "skipped" elements) were pruned.
+    Pruned {
+        /// How many unread logical entries were pruned.
+        missed: usize,
+    },
+}
+
+impl fmt::Display for CursorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CursorError::Pruned { missed } => {
+                write!(f, "{missed} unread entries were pruned")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CursorError {}
+
+/// A saved position in a [`ChunkedData`], used to only read the elements pushed
+/// since the last read with [`ChunkedCursor::next_new`].
+///
+/// The cursor keeps track of its position across prunes (e.g. [`ChunkedData::prune`]),
+/// but not other removals like [`ChunkedData::drain`]. A cursor should only be used
+/// with the [`ChunkedData`] it was created from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChunkedCursor {
+    /// The position, counting every entry that was ever pruned.
+    position: usize,
+}
+
+impl<D, S: ChunkStorage<D>> ChunkedData<D, S> {
+    /// Return a [`ChunkedCursor`] positioned at the current length, so it will only
+    /// read elements pushed after this.
+    pub fn cursor(&self) -> ChunkedCursor {
+        self.cursor_at(self.next_index)
+    }
+
+    /// Return a [`ChunkedCursor`] positioned at the logical `index`, so it will read
+    /// elements from `index` onwards. The index is clamped to the length.
+    pub fn cursor_at(&self, index: usize) -> ChunkedCursor {
+        ChunkedCursor {
+            position: self.num_pruned + std::cmp::min(index, self.next_index),
+        }
+    }
+}
+
+impl ChunkedCursor {
+    /// Returns an iterator of the elements alongside their indices from the cursor's
+    /// position up to the current length of `data`, and moves the cursor to the end.
+    ///
+    /// If any of the indices before the cursor were pruned, the position is shifted to
+    /// match. If any unread entries were pruned, a [`CursorError`] is returned instead,
+    /// and the cursor is moved to the start of `data` so that the next call will read
+    /// what is left.
+    pub fn next_new<'a, D, S: ChunkStorage<D>>(
+        &mut self, data: &'a ChunkedData<D, S>,
+    ) -> Result<
+        ChunkedDataIter<impl DoubleEndedIterator<Item = (usize, &'a D)> + use<'a, D, S>>,
+        CursorError,
+    > {
+        let Some(start) = self.position.checked_sub(data.num_pruned) else {
+            let missed = data.num_pruned - self.position;
+            self.position = data.num_pruned;
+
+            return Err(CursorError::Pruned { missed });
+        };
+
+        let start = std::cmp::min(start, data.next_index);
+        self.position = data.num_pruned + data.next_index;
+
+        let slices = data.slices_in_range(start..data.next_index);
+        let size = slices.clone().map(|(_, data)| data.len()).sum();
+        let iter = slices.flat_map(|(start, data)| {
+            data.iter()
+                .enumerate()
+                .map(move |(offset, datum)| (start + offset, datum))
+        });
+
+        Ok(ChunkedDataIter { iter, size })
+    }
+
+    /// Return the logical index in `data` that the next read will start from, or
+    /// [`None`] if unread entries were pruned.
+    pub fn index<D, S>(&self, data: &ChunkedData<D, S>) -> Option<usize> {
+        self.position.checked_sub(data.num_pruned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(cursor: &mut ChunkedCursor, data: &ChunkedData<u64>) -> Vec<(usize, u64)> {
+        cursor
+            .next_new(data)
+            .unwrap()
+            .map(|(index, datum)| (index, *datum))
+            .collect()
+    }
+
+    #[test]
+    fn cursor_reads_new() {
+        let mut data = ChunkedData::<u64>::default();
+        data.push(1);
+
+        let mut from_start = data.cursor_at(0);
+        let mut cursor = data.cursor();
+        assert_eq!(collect(&mut cursor, &data), vec![]);
+
+        data.push(2);
+        data.try_push(None);
+        data.push(4);
+
+        let iter = cursor.next_new(&data).unwrap();
+        assert_eq!(iter.len(), 2);
+        assert_eq!(
+            iter.map(|(index, datum)| (index, *datum))
+                .collect::<Vec<_>>(),
+            vec![(1, 2), (3, 4)]
+        );
+        assert_eq!(collect(&mut cursor, &data), vec![]);
+        assert_eq!(cursor.index(&data), Some(4));
+
+        assert_eq!(
+            collect(&mut from_start, &data),
+            vec![(0, 1), (1, 2), (3, 4)]
+        );
+
+        // Past the end is clamped.
+        let mut cursor = data.cursor_at(100);
+        data.push(5);
+        assert_eq!(collect(&mut cursor, &data), vec![(4, 5)]);
+    }
+
+    /// Pruning entries that were already read should shift the cursor.
+    #[test]
+    fn cursor_rebases_after_prune() {
+        let mut data = ChunkedData::<u64>::default();
+        let mut cursor = data.cursor();
+        let mut seen = Vec::new();
+
+        for cycle in 0..10u64 {
+            for i in 0..5 {
+                if i == 2 {
+                    data.try_push(None);
+                } else {
+                    data.push(cycle * 5 + i);
+                }
+            }
+
+            seen.extend(cursor.next_new(&data).unwrap().map(|(_, datum)| *datum));
+
+            // Only keep the latest few entries around.
+            if data.length() > 7 {
+                data.prune(data.length() - 8).unwrap();
+            }
+
+            assert_eq!(cursor.index(&data), Some(data.length()));
+        }
+
+        let expected = (0..50).filter(|i| i % 5 != 2).collect::<Vec<_>>();
+        assert_eq!(seen, expected);
+
+        data.prune_all();
+        data.push(50);
+        assert_eq!(collect(&mut cursor, &data), vec![(0, 50)]);
+    }
+
+    /// Pruning entries that were not read yet should return an error.
+    #[test]
+    fn cursor_errors_after_missed_prune() {
+        let mut data = ChunkedData::<u64>::default();
+        data.push(0);
+
+        let mut cursor = data.cursor();
+        for i in 1..6 {
+            data.push(i);
+        }
+
+        data.prune(2).unwrap();
+        assert_eq!(cursor.index(&data), None);
+        assert_eq!(
+            cursor.next_new(&data).err(),
+            Some(CursorError::Pruned { missed: 2 })
+        );
+
+        // The cursor continues from what's left.
+        assert_eq!(cursor.index(&data), Some(0));
+        assert_eq!(collect(&mut cursor, &data), vec![(0, 3), (1, 4), (2, 5)]);
+
+        data.push(6);
+        data.prune_all();
+        assert_eq!(
+            cursor.next_new(&data).err(),
+            Some(CursorError::Pruned { missed: 1 })
+        );
+        assert_eq!(collect(&mut cursor, &data), vec![]);
+
+        assert_eq!(
+            CursorError::Pruned { missed: 2 }.to_string(),
+            "2 unread entries were pruned"
+        );
+    }
+}
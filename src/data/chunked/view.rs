@@ -0,0 +1,230 @@
+//! A read-only view over a [`ChunkedData`].
+
+use std::ops::Range;
+
+use super::{ChunkedData, ChunkedDataIter};
+use crate::data::ChunkStorage;
+
+/// A borrowed, read-only view over some logical range of a [`ChunkedData`].
+///
+/// Indices are _not_ rebased; an element at index `i` in the [`ChunkedData`] is
+/// also at index `i` in the view.
+pub struct ChunkedDataView<'a, D, S = Vec<D>> {
+    data: &'a ChunkedData<D, S>,
+    range: Range<usize>,
+}
+
+impl<D, S> Clone for ChunkedDataView<'_, D, S> {
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data,
+            range: self.range.clone(),
+        }
+    }
+}
+
+impl<D, S: ChunkStorage<D>> ChunkedData<D, S> {
+    /// Return a read-only view over the entire [`ChunkedData`].
+    pub fn view(&self) -> ChunkedDataView<'_, D, S> {
+        self.view_range(0..self.length())
+    }
+
+    /// Return a read-only view over the logical `range` of the [`ChunkedData`].
+    /// The range is clamped to the length of the [`ChunkedData`].
+    pub fn view_range(&self, range: Range<usize>) -> ChunkedDataView<'_, D, S> {
+        let end = std::cmp::min(range.end, self.length());
+        let start = std::cmp::min(range.start, end);
+
+        ChunkedDataView {
+            data: self,
+            range: start..end,
+        }
+    }
+}
+
+impl<'a, D, S: ChunkStorage<D>> ChunkedDataView<'a, D, S> {
+    /// Return the logical range this view covers.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Returns an iterator of items alongside the associated indices for each item.
+    pub fn iter_with_index(
+        &self,
+    ) -> ChunkedDataIter<impl DoubleEndedIterator<Item = (usize, &'a D)> + use<'a, D, S>> {
+        let slices = self.data.slices_in_range(self.range.clone());
+        let size = slices.clone().map(|(_, data)| data.len()).sum();
+        let iter = slices.flat_map(|(start, data)| {
+            data.iter()
+                .enumerate()
+                .map(move |(offset, datum)| (start + offset, datum))
+        });
+
+        ChunkedDataIter { iter, size }
+    }
+
+    /// Returns an iterator of items.
+    pub fn iter(&self) -> ChunkedDataIter<impl DoubleEndedIterator<Item = &'a D> + use<'a, D, S>> {
+        let slices = self.data.slices_in_range(self.range.clone());
+        let size = slices.clone().map(|(_, data)| data.len()).sum();
+        let iter = slices.flat_map(|(_, data)| data.iter());
+
+        ChunkedDataIter { iter, size }
+    }
+
+    /// Return the element at `index`, or [`None`] if `index` is a gap or outside of the view.
+    pub fn get(&self, index: usize) -> Option<&'a D> {
+        if self.range.contains(&index) {
+            self.data.get(index)
+        } else {
+            None
+        }
+    }
+
+    /// Return how many elements actually are stored in the view.
+    pub fn num_elements(&self) -> usize {
+        self.data
+            .slices_in_range(self.range.clone())
+            .map(|(_, data)| data.len())
+            .sum()
+    }
+
+    /// Return the "length" of the view, _including_ skipped elements.
+    pub fn length(&self) -> usize {
+        self.range.len()
+    }
+
+    /// Try and return the first element in the view.
+    pub fn first(&self) -> Option<&'a D> {
+        self.data
+            .slices_in_range(self.range.clone())
+            .find_map(|(_, data)| data.first())
+    }
+
+    /// Try and return the last element in the view.
+    pub fn last(&self) -> Option<&'a D> {
+        self.data
+            .slices_in_range(self.range.clone())
+            .rev()
+            .find_map(|(_, data)| data.last())
+    }
+
+    /// Return whether there are zero elements stored in the view.
+    pub fn no_elements(&self) -> bool {
+        self.num_elements() == 0
+    }
+}
+
+/// Implements the float-specific helpers for [`ChunkedDataView`].
+macro_rules! impl_float_view_helpers {
+    ($($t:ty),*) => {
+        $(
+            impl<S: ChunkStorage<$t>> ChunkedDataView<'_, $t, S> {
+                /// Return the minimum and maximum of the elements in the view, or [`None`] if
+                /// there are none. NaN values are ignored.
+                ///
+                /// See [`ChunkedData::min_max_in_range`].
+                pub fn min_max(&self) -> Option<(f64, f64)> {
+                    self.data.min_max_in_range(self.range.clone())
+                }
+            }
+        )*
+    };
+}
+
+impl_float_view_helpers!(f32, f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::chunked::tests::{POPULATION, test_populate};
+
+    fn populated() -> ChunkedData<u64> {
+        let mut data = ChunkedData::default();
+        test_populate(&mut data);
+
+        data
+    }
+
+    #[test]
+    fn full_view() {
+        let data = populated();
+        let view = data.view();
+
+        assert_eq!(view.range(), 0..10);
+        assert_eq!(view.length(), data.length());
+        assert_eq!(view.num_elements(), data.num_elements());
+        assert!(view.iter_with_index().eq(data.iter_with_index()));
+        assert!(view.iter().eq(data.iter()));
+        assert_eq!(view.first(), Some(&1));
+        assert_eq!(view.last(), Some(&10));
+    }
+
+    /// A view spanning a gap should behave like filtering the full iterator.
+    #[test]
+    fn range_view_over_gap() {
+        let data = populated();
+
+        for start in 0..=POPULATION.len() {
+            for end in start..=POPULATION.len() {
+                let view = data.view_range(start..end);
+                let expected = data
+                    .iter_with_index()
+                    .filter(|(index, _)| (start..end).contains(index))
+                    .collect::<Vec<_>>();
+
+                assert_eq!(view.length(), end - start);
+                assert_eq!(view.iter_with_index().len(), expected.len());
+                assert_eq!(view.num_elements(), expected.len());
+                assert_eq!(view.iter_with_index().collect::<Vec<_>>(), expected);
+                assert_eq!(
+                    view.iter_with_index().rev().collect::<Vec<_>>(),
+                    expected.iter().copied().rev().collect::<Vec<_>>()
+                );
+                assert_eq!(view.first(), expected.first().map(|(_, v)| *v));
+                assert_eq!(view.last(), expected.last().map(|(_, v)| *v));
+
+                for index in 0..POPULATION.len() {
+                    let expected = if (start..end).contains(&index) {
+                        data.get(index)
+                    } else {
+                        None
+                    };
+                    assert_eq!(view.get(index), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn range_view_clamps() {
+        let data = populated();
+
+        let view = data.view_range(5..100);
+        assert_eq!(view.range(), 5..10);
+        assert_eq!(view.iter().copied().collect::<Vec<_>>(), vec![7, 8, 9, 10]);
+
+        let view = data.view_range(50..100);
+        assert_eq!(view.range(), 10..10);
+        assert!(view.no_elements());
+
+        let view = data.view_range(3..6);
+        assert_eq!(view.length(), 3);
+        assert!(view.no_elements());
+        assert_eq!(view.first(), None);
+    }
+
+    #[test]
+    fn view_min_max() {
+        let mut data = ChunkedData::<f64>::default();
+        data.push(3.0);
+        data.push(-1.0);
+        data.try_push(None);
+        data.push(10.0);
+        data.push(f64::NAN);
+
+        assert_eq!(data.view().min_max(), Some((-1.0, 10.0)));
+        assert_eq!(data.view_range(1..3).min_max(), Some((-1.0, -1.0)));
+        assert_eq!(data.view_range(2..3).min_max(), None);
+    }
+}
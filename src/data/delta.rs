@@ -0,0 +1,549 @@
+//! This is code responsible for possibly chunked data that is stored
+//! delta encoded, for integer values that change by small amounts.
+
+use std::marker::PhantomData;
+
+use super::{ChunkedDataIter, PruneError};
+
+/// An unsigned integer type that can be stored in a [`DeltaChunkedData`].
+pub trait DeltaEncodable: Copy {
+    /// Convert the value into a [`u64`], without any loss.
+    fn to_u64(self) -> u64;
+
+    /// Convert a [`u64`] that was created with [`DeltaEncodable::to_u64`] back into
+    /// the value.
+    fn from_u64(value: u64) -> Self;
+}
+
+/// Implements [`DeltaEncodable`] for unsigned integer types.
+macro_rules! impl_delta_encodable {
+    ($($t:ty),*) => {
+        $(
+            impl DeltaEncodable for $t {
+                fn to_u64(self) -> u64 {
+                    self as u64
+                }
+
+                fn from_u64(value: u64) -> Self {
+                    value as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_delta_encodable!(u8, u16, u32, u64, usize);
+
+/// Append the difference between `prev` and `next` to `buf`, as a zig-zag encoded varint.
+fn encode_delta(buf: &mut Vec<u8>, prev: u64, next: u64) {
+    let delta = next.wrapping_sub(prev) as i64;
+    let mut zigzag = ((delta << 1) ^ (delta >> 63)) as u64;
+
+    while zigzag >= 0x80 {
+        buf.push((zigzag as u8) | 0x80);
+        zigzag >>= 7;
+    }
+
+    buf.push(zigzag as u8);
+}
+
+/// Decode the varint at the start of `bytes`, returning the delta and how many bytes it used.
+fn decode_delta(bytes: &[u8]) -> (u64, usize) {
+    let mut zigzag = 0;
+
+    for (index, &byte) in bytes.iter().enumerate() {
+        zigzag |= u64::from(byte & 0x7f) << (7 * index);
+
+        if byte & 0x80 == 0 {
+            let delta = ((zigzag >> 1) as i64) ^ -((zigzag & 1) as i64);
+            return (delta as u64, index + 1);
+        }
+    }
+
+    unreachable!("the deltas must end with a complete varint");
+}
+
+/// Decode the varint at the end of `bytes`, returning the delta and where it starts.
+fn decode_delta_back(bytes: &[u8]) -> (u64, usize) {
+    // Every byte but the last of a varint has the continuation bit set, so the
+    // varint starts right after the previous byte without it.
+    let start = bytes[..bytes.len() - 1]
+        .iter()
+        .rposition(|&byte| byte & 0x80 == 0)
+        .map_or(0, |index| index + 1);
+
+    (decode_delta(&bytes[start..]).0, start)
+}
+
+#[derive(Clone, Debug)]
+struct DeltaChunk {
+    /// The start offset of this chunk, should correspond to the time vector
+    /// indices. If that updates, this MUST also update.
+    start_offset: usize,
+
+    /// The number of elements in this chunk.
+    len: usize,
+
+    /// The first value of the chunk.
+    first: u64,
+
+    /// The last value of the chunk, which new deltas are based on.
+    last: u64,
+
+    /// The differences between each consecutive value, as zig-zag encoded varints.
+    deltas: Vec<u8>,
+}
+
+impl DeltaChunk {
+    fn new(start_offset: usize, value: u64) -> Self {
+        Self {
+            start_offset,
+            len: 1,
+            first: value,
+            last: value,
+            deltas: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, value: u64) {
+        encode_delta(&mut self.deltas, self.last, value);
+        self.last = value;
+        self.len += 1;
+    }
+
+    /// Remove the first `count` elements; `count` must be less than the length.
+    fn drain_front(&mut self, count: usize) {
+        let mut pos = 0;
+
+        for _ in 0..count {
+            let (delta, used) = decode_delta(&self.deltas[pos..]);
+            self.first = self.first.wrapping_add(delta);
+            pos += used;
+        }
+
+        self.deltas.drain(..pos);
+        self.len -= count;
+    }
+
+    fn iter(&self) -> DeltaChunkIter<'_> {
+        DeltaChunkIter {
+            deltas: &self.deltas,
+            front: self.first,
+            back: self.last,
+            remaining: self.len,
+        }
+    }
+}
+
+/// Decodes the values of a [`DeltaChunk`] from either end.
+struct DeltaChunkIter<'a> {
+    /// The deltas that have not been decoded yet.
+    deltas: &'a [u8],
+    front: u64,
+    back: u64,
+    remaining: usize,
+}
+
+impl Iterator for DeltaChunkIter<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let value = self.front;
+        self.remaining -= 1;
+
+        if self.remaining > 0 {
+            let (delta, used) = decode_delta(self.deltas);
+            self.deltas = &self.deltas[used..];
+            self.front = self.front.wrapping_add(delta);
+        }
+
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for DeltaChunkIter<'_> {}
+
+impl DoubleEndedIterator for DeltaChunkIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let value = self.back;
+        self.remaining -= 1;
+
+        if self.remaining > 0 {
+            let (delta, start) = decode_delta_back(self.deltas);
+            self.deltas = &self.deltas[..start];
+            self.back = self.back.wrapping_sub(delta);
+        }
+
+        Some(value)
+    }
+}
+
+/// A struct representing data that may potentially have breaks, like a
+/// [`crate::data::ChunkedData`], but where each chunk only stores its first
+/// value in whole, followed by the differences between consecutive values.
+///
+/// This is useful for integer data that only changes by small amounts, like
+/// counters. Values are decoded on the fly, so they are returned by value
+/// rather than by reference.
+#[derive(Clone, Debug)]
+pub struct DeltaChunkedData<T> {
+    next_index: usize,
+    is_active: bool,
+    chunks: Vec<DeltaChunk>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Default for DeltaChunkedData<T> {
+    fn default() -> Self {
+        Self {
+            next_index: 0,
+            is_active: false,
+            chunks: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: DeltaEncodable> DeltaChunkedData<T> {
+    /// Returns an iterator of items alongside the associated indices for each item.
+    pub fn iter_with_index(&self) -> ChunkedDataIter<impl DoubleEndedIterator<Item = (usize, T)>> {
+        let size = self.num_elements();
+        let iter = self.chunks.iter().flat_map(|dc| {
+            let start = dc.start_offset;
+
+            (start..start + dc.len)
+                .zip(dc.iter())
+                .map(|(index, value)| (index, T::from_u64(value)))
+        });
+
+        ChunkedDataIter { iter, size }
+    }
+
+    /// Returns an iterator of items.
+    pub fn iter(&self) -> ChunkedDataIter<impl DoubleEndedIterator<Item = T>> {
+        let size = self.num_elements();
+        let iter = self.chunks.iter().flat_map(|dc| dc.iter().map(T::from_u64));
+
+        ChunkedDataIter { iter, size }
+    }
+
+    /// Return how many elements actually are stored in the [`DeltaChunkedData`].
+    pub fn num_elements(&self) -> usize {
+        self.chunks.iter().map(|dc| dc.len).sum()
+    }
+
+    /// Return how many bytes are used to store the encoded differences between values.
+    pub fn num_encoded_bytes(&self) -> usize {
+        self.chunks.iter().map(|dc| dc.deltas.len()).sum()
+    }
+
+    /// Return the "length" of the [`DeltaChunkedData`], _including_ skipped
+    /// elements.
+    pub fn length(&self) -> usize {
+        self.next_index
+    }
+
+    /// Push an element.
+    pub fn push(&mut self, item: T) {
+        let value = item.to_u64();
+
+        if self.is_active {
+            let current_chunk = self
+                .chunks
+                .last_mut()
+                .expect("chunks must be initialized with at least a value if is_active is set");
+            current_chunk.push(value);
+        } else {
+            // Start a new chunk.
+            self.chunks.push(DeltaChunk::new(self.next_index, value));
+            self.is_active = true;
+        }
+
+        self.next_index += 1;
+    }
+
+    /// Manually mark that a break is needed in the chunk.
+    pub fn insert_break(&mut self) {
+        // "Seal" the latest chunk.
+        self.is_active = false;
+    }
+
+    /// Push an element. If `item` is [`None`], then it will automatically
+    /// insert a break in the chunk if needed.
+    pub fn try_push(&mut self, item: Option<T>) {
+        match item {
+            Some(item) => {
+                self.push(item);
+            }
+            None => {
+                self.insert_break();
+                self.next_index += 1;
+            }
+        }
+    }
+
+    /// Remove all elements up to (and including) `index`, including "skipped"
+    /// elements. This will result in the effective length becoming
+    /// `prev_length - index - 1`.
+    ///
+    /// Whole chunks are dropped without decoding them; only a chunk that is partially
+    /// pruned needs to be decoded up to `index`.
+    ///
+    /// If there are no stored elements, or `index` goes past the length, this function
+    /// will return a [`PruneError`].
+    pub fn prune(&mut self, index: usize) -> Result<(), PruneError> {
+        PruneError::check(index, self.next_index, self.chunks.is_empty())?;
+
+        let removed = index + 1;
+        self.next_index -= removed;
+
+        // Remove all chunks that are entirely before the cutoff.
+        let to_drain = self
+            .chunks
+            .partition_point(|dc| dc.start_offset + dc.len <= removed);
+        self.chunks.drain(..to_drain);
+
+        if self.chunks.is_empty() {
+            self.is_active = false;
+        }
+
+        for chunk in &mut self.chunks {
+            if chunk.start_offset < removed {
+                chunk.drain_front(removed - chunk.start_offset);
+                chunk.start_offset = 0;
+            } else {
+                chunk.start_offset -= removed;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Shrink the [`DeltaChunkedData`] after.
+    pub fn shrink_to_fit(&mut self) {
+        for chunk in &mut self.chunks {
+            chunk.deltas.shrink_to_fit();
+        }
+
+        self.chunks.shrink_to_fit();
+    }
+
+    /// Return the element at `index`, or [`None`] if `index` is a gap or out of range.
+    ///
+    /// Note this has to decode the chunk containing `index` up to `index`.
+    pub fn get(&self, index: usize) -> Option<T> {
+        let chunk = &self.chunks[self
+            .chunks
+            .partition_point(|dc| dc.start_offset <= index)
+            .checked_sub(1)?];
+
+        chunk
+            .iter()
+            .nth(index - chunk.start_offset)
+            .map(T::from_u64)
+    }
+
+    /// Try and return the first element.
+    pub fn first(&self) -> Option<T> {
+        self.chunks.first().map(|dc| T::from_u64(dc.first))
+    }
+
+    /// Try and return the last element.
+    pub fn last(&self) -> Option<T> {
+        self.chunks.last().map(|dc| T::from_u64(dc.last))
+    }
+
+    /// Return whether there are zero elements left stored internally.
+    pub fn no_elements(&self) -> bool {
+        self.chunks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ChunkedData;
+
+    const POPULATION: [Option<u64>; 14] = [
+        Some(1_000_000),
+        Some(1_000_100),
+        Some(1_000_050),
+        Some(1_000_050),
+        None,
+        None,
+        Some(5),
+        Some(u64::MAX),
+        Some(0),
+        Some(300),
+        Some(200),
+        None,
+        Some(7),
+        Some(8),
+    ];
+
+    fn test_populate(delta: &mut DeltaChunkedData<u64>, plain: &mut ChunkedData<u64>) {
+        for p in POPULATION {
+            delta.try_push(p);
+            plain.try_push(p);
+        }
+    }
+
+    #[test]
+    fn delta_push() {
+        let mut data = DeltaChunkedData::default();
+        assert!(data.no_elements());
+
+        data.push(100u64);
+        data.push(101);
+        data.push(99);
+        assert_eq!(data.chunks.len(), 1);
+        assert_eq!(data.chunks[0].deltas, vec![2, 3]);
+
+        data.try_push(None);
+        data.push(2);
+        assert_eq!(data.chunks.len(), 2);
+        assert_eq!(data.chunks[1].start_offset, 4);
+
+        assert_eq!(data.length(), 5);
+        assert_eq!(data.num_elements(), 4);
+        assert_eq!(data.num_encoded_bytes(), 2);
+    }
+
+    #[test]
+    fn delta_matches_chunked() {
+        let mut delta = DeltaChunkedData::default();
+        let mut plain = ChunkedData::default();
+        test_populate(&mut delta, &mut plain);
+
+        assert_eq!(delta.length(), plain.length());
+        assert_eq!(delta.num_elements(), plain.num_elements());
+        assert_eq!(delta.first().as_ref(), plain.first());
+        assert_eq!(delta.last().as_ref(), plain.last());
+
+        assert_eq!(delta.iter().len(), plain.iter().len());
+        assert!(delta.iter().eq(plain.iter().copied()));
+        assert!(
+            delta.iter_with_index().eq(plain
+                .iter_with_index()
+                .map(|(index, value)| (index, *value)))
+        );
+        assert!(
+            delta.iter_with_index().rev().eq(plain
+                .iter_with_index()
+                .rev()
+                .map(|(index, value)| (index, *value)))
+        );
+
+        for index in 0..=POPULATION.len() {
+            assert_eq!(
+                delta.get(index).as_ref(),
+                plain.get(index),
+                "getting {index}"
+            );
+        }
+    }
+
+    /// Iterating from both ends should meet in the middle.
+    #[test]
+    fn delta_iter_both_ends() {
+        let mut data = DeltaChunkedData::default();
+        for value in [10u32, 1000, 3, 70000, 70001] {
+            data.push(value);
+        }
+
+        let mut iter = data.iter();
+        assert_eq!(iter.next(), Some(10));
+        assert_eq!(iter.next_back(), Some(70001));
+        assert_eq!(iter.next_back(), Some(70000));
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.next(), Some(1000));
+        assert_eq!(iter.next_back(), Some(3));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn delta_prune() {
+        for index in 0..POPULATION.len() {
+            let mut data = DeltaChunkedData::default();
+            for p in POPULATION {
+                data.try_push(p);
+            }
+
+            assert!(data.prune(index).is_ok());
+
+            let expected = POPULATION
+                .into_iter()
+                .skip(index + 1)
+                .enumerate()
+                .filter_map(|(a, b)| b.map(|b| (a, b)))
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                data.iter_with_index().collect::<Vec<_>>(),
+                expected,
+                "pruning up to {index} should match"
+            );
+            assert_eq!(data.length(), POPULATION.len() - index - 1);
+            assert_eq!(data.num_elements(), expected.len());
+            assert_eq!(data.first(), expected.first().map(|(_, value)| *value));
+
+            // Pushing after pruning should continue from the new length.
+            data.push(9);
+            assert_eq!(
+                data.iter_with_index().next_back(),
+                Some((data.length() - 1, 9))
+            );
+        }
+    }
+
+    #[test]
+    fn delta_prune_invalid() {
+        let mut data = DeltaChunkedData::<u64>::default();
+        assert_eq!(data.prune(0), Err(PruneError::Empty));
+
+        data.push(1);
+        assert_eq!(
+            data.prune(1),
+            Err(PruneError::IndexOutOfBounds {
+                requested: 1,
+                max: 0
+            })
+        );
+    }
+
+    /// A slowly increasing counter should take much less memory than storing each value.
+    #[test]
+    fn delta_memory_usage() {
+        let mut delta = DeltaChunkedData::default();
+        let mut plain = ChunkedData::<u64>::default();
+
+        let mut value = 1u64 << 40;
+        for i in 0..1000 {
+            value += i % 50;
+            delta.push(value);
+            plain.push(value);
+        }
+
+        assert!(delta.iter().eq(plain.iter().copied()));
+
+        // Each delta fits in a single byte.
+        assert_eq!(delta.num_encoded_bytes(), 999);
+
+        let delta_bytes = delta.num_encoded_bytes() + 2 * std::mem::size_of::<u64>();
+        let plain_bytes = plain.num_elements() * std::mem::size_of::<u64>();
+        assert!(delta_bytes * 7 < plain_bytes);
+    }
+}
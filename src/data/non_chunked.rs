@@ -5,4 +5,38 @@
 /// A struct representing data that will not have any breaks;
 /// if you use this, you are assuming each time will have a
 /// corresponding value.
+#[derive(Clone, Default, Debug, PartialEq, Eq, Hash)]
 pub struct NonChunkedData<T>(Vec<T>);
+
+impl<T> NonChunkedData<T> {
+    /// Push an element.
+    pub fn push(&mut self, item: T) {
+        self.0.push(item);
+    }
+
+    /// Return the number of stored elements.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Return whether there are no stored elements.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Return the stored elements as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        &self.0
+    }
+
+    /// Consume the [`NonChunkedData`], returning the underlying [`Vec`].
+    pub fn into_vec(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> From<Vec<T>> for NonChunkedData<T> {
+    fn from(items: Vec<T>) -> Self {
+        Self(items)
+    }
+}
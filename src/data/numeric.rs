@@ -0,0 +1,26 @@
+//! Conversions of stored values into floats, for methods that do arithmetic on them.
+
+/// A numeric type that can be converted into an [`f64`], for calculations like
+/// [`crate::data::ChunkedData::moving_average`].
+///
+/// Unlike [`Into<f64>`], this is also implemented for 64-bit integers such as a
+/// cumulative byte counter, at the cost of precision past 2<sup>53</sup>.
+pub trait ToF64: Copy {
+    /// Convert the value into an [`f64`], rounding to the nearest representable value.
+    fn to_f64(self) -> f64;
+}
+
+/// Implements [`ToF64`] for primitive numeric types.
+macro_rules! impl_to_f64 {
+    ($($t:ty),*) => {
+        $(
+            impl ToF64 for $t {
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_to_f64!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
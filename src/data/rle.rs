@@ -0,0 +1,403 @@
+//! This is code responsible for possibly chunked data that is stored
+//! run-length encoded, for values that rarely change.
+
+use super::{ChunkedDataIter, PruneError};
+
+#[derive(Clone, Debug, PartialEq)]
+struct Run<D> {
+    value: D,
+
+    /// The offset of the first element of this run within the chunk.
+    offset: usize,
+
+    /// How many times the value repeats.
+    count: usize,
+}
+
+#[derive(Clone, Default, Debug)]
+struct RleChunk<D> {
+    /// The start offset of this chunk, should correspond to the time vector
+    /// indices. If that updates, this MUST also update.
+    start_offset: usize,
+
+    /// The number of logical elements in this chunk.
+    len: usize,
+
+    /// The runs of values.
+    runs: Vec<Run<D>>,
+}
+
+impl<D: PartialEq> RleChunk<D> {
+    fn push(&mut self, item: D) {
+        match self.runs.last_mut() {
+            Some(run) if run.value == item => run.count += 1,
+            _ => self.runs.push(Run {
+                value: item,
+                offset: self.len,
+                count: 1,
+            }),
+        }
+
+        self.len += 1;
+    }
+
+    /// Remove the first `count` elements, splitting a run if needed.
+    fn drain_front(&mut self, count: usize) {
+        let full_runs = self
+            .runs
+            .partition_point(|run| run.offset + run.count <= count);
+        self.runs.drain(..full_runs);
+
+        if let Some(run) = self.runs.first_mut()
+            && run.offset < count
+        {
+            // Split the run.
+            run.count -= count - run.offset;
+            run.offset = count;
+        }
+
+        for run in &mut self.runs {
+            run.offset -= count;
+        }
+
+        self.len -= count;
+    }
+}
+
+/// A struct representing data that may potentially have breaks, like a
+/// [`crate::data::ChunkedData`], but where repeated consecutive values
+/// are only stored once alongside how many times they repeat.
+///
+/// This is useful for data that holds the same value for long stretches.
+#[derive(Clone, Debug)]
+pub struct RleChunkedData<D> {
+    next_index: usize,
+    is_active: bool,
+    chunks: Vec<RleChunk<D>>,
+}
+
+// This isn't derived, as that would require `D: Default`.
+impl<D> Default for RleChunkedData<D> {
+    fn default() -> Self {
+        Self {
+            next_index: 0,
+            is_active: false,
+            chunks: Vec::new(),
+        }
+    }
+}
+
+impl<D: PartialEq> RleChunkedData<D> {
+    /// Returns an iterator of items alongside the associated indices for each item.
+    pub fn iter_with_index(&self) -> ChunkedDataIter<impl DoubleEndedIterator<Item = (usize, &D)>> {
+        let size = self.num_elements();
+        let iter = self.chunks.iter().flat_map(|rc| {
+            let start = rc.start_offset;
+
+            rc.runs.iter().flat_map(move |run| {
+                let run_start = start + run.offset;
+
+                (run_start..run_start + run.count).map(move |index| (index, &run.value))
+            })
+        });
+
+        ChunkedDataIter { iter, size }
+    }
+
+    /// Returns an iterator of items.
+    pub fn iter(&self) -> ChunkedDataIter<impl DoubleEndedIterator<Item = &D>> {
+        let size = self.num_elements();
+        let iter = self.chunks.iter().flat_map(|rc| {
+            rc.runs
+                .iter()
+                .flat_map(|run| std::iter::repeat_n(&run.value, run.count))
+        });
+
+        ChunkedDataIter { iter, size }
+    }
+
+    /// Return how many elements actually are stored in the [`RleChunkedData`].
+    pub fn num_elements(&self) -> usize {
+        self.chunks.iter().map(|rc| rc.len).sum()
+    }
+
+    /// Return how many runs of repeated values are stored in the [`RleChunkedData`].
+    pub fn num_runs(&self) -> usize {
+        self.chunks.iter().map(|rc| rc.runs.len()).sum()
+    }
+
+    /// Return the "length" of the [`RleChunkedData`], _including_ skipped
+    /// elements.
+    pub fn length(&self) -> usize {
+        self.next_index
+    }
+
+    /// Push an element.
+    pub fn push(&mut self, item: D) {
+        if self.is_active {
+            let current_chunk = self
+                .chunks
+                .last_mut()
+                .expect("chunks must be initialized with at least a value if is_active is set");
+            current_chunk.push(item);
+        } else {
+            // Start a new chunk.
+            self.chunks.push(RleChunk {
+                start_offset: self.next_index,
+                len: 1,
+                runs: vec![Run {
+                    value: item,
+                    offset: 0,
+                    count: 1,
+                }],
+            });
+            self.is_active = true;
+        }
+
+        self.next_index += 1;
+    }
+
+    /// Manually mark that a break is needed in the chunk.
+    pub fn insert_break(&mut self) {
+        // "Seal" the latest chunk.
+        self.is_active = false;
+    }
+
+    /// Push an element. If `item` is [`None`], then it will automatically
+    /// insert a break in the chunk if needed.
+    pub fn try_push(&mut self, item: Option<D>) {
+        match item {
+            Some(item) => {
+                self.push(item);
+            }
+            None => {
+                self.insert_break();
+                self.next_index += 1;
+            }
+        }
+    }
+
+    /// Remove all elements up to (and including) `index`, including "skipped"
+    /// elements. This will result in the effective length becoming
+    /// `prev_length - index - 1`.
+    ///
+    /// If there are no stored elements, or `index` goes past the length, this function
+    /// will return a [`PruneError`].
+    pub fn prune(&mut self, index: usize) -> Result<(), PruneError> {
+        PruneError::check(index, self.next_index, self.chunks.is_empty())?;
+
+        let removed = index + 1;
+        self.next_index -= removed;
+
+        // Remove all chunks that are entirely before the cutoff.
+        let to_drain = self
+            .chunks
+            .partition_point(|rc| rc.start_offset + rc.len <= removed);
+        self.chunks.drain(..to_drain);
+
+        if self.chunks.is_empty() {
+            self.is_active = false;
+        }
+
+        for chunk in &mut self.chunks {
+            if chunk.start_offset < removed {
+                chunk.drain_front(removed - chunk.start_offset);
+                chunk.start_offset = 0;
+            } else {
+                chunk.start_offset -= removed;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Shrink the [`RleChunkedData`] after.
+    pub fn shrink_to_fit(&mut self) {
+        for chunk in &mut self.chunks {
+            chunk.runs.shrink_to_fit();
+        }
+
+        self.chunks.shrink_to_fit();
+    }
+
+    /// Try and return the first element.
+    pub fn first(&self) -> Option<&D> {
+        self.chunks
+            .first()
+            .and_then(|chunk| chunk.runs.first())
+            .map(|run| &run.value)
+    }
+
+    /// Try and return the last element.
+    pub fn last(&self) -> Option<&D> {
+        self.chunks
+            .last()
+            .and_then(|chunk| chunk.runs.last())
+            .map(|run| &run.value)
+    }
+
+    /// Return whether there are zero elements left stored internally.
+    pub fn no_elements(&self) -> bool {
+        self.chunks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::ChunkedData;
+
+    const POPULATION: [Option<u64>; 14] = [
+        Some(1),
+        Some(1),
+        Some(1),
+        Some(2),
+        None,
+        None,
+        Some(2),
+        Some(2),
+        Some(3),
+        Some(3),
+        Some(3),
+        None,
+        Some(3),
+        Some(4),
+    ];
+
+    fn test_populate(rle: &mut RleChunkedData<u64>, plain: &mut ChunkedData<u64>) {
+        for p in POPULATION {
+            rle.try_push(p);
+            plain.try_push(p);
+        }
+    }
+
+    /// Return the runs of a chunk as `(value, count)` pairs.
+    fn runs(chunk: &RleChunk<u64>) -> Vec<(u64, usize)> {
+        chunk
+            .runs
+            .iter()
+            .map(|run| (run.value, run.count))
+            .collect()
+    }
+
+    #[test]
+    fn rle_push() {
+        let mut data = RleChunkedData::default();
+        assert!(data.no_elements());
+
+        data.push(1);
+        data.push(1);
+        data.push(2);
+        assert_eq!(data.chunks.len(), 1);
+        assert_eq!(runs(&data.chunks[0]), vec![(1, 2), (2, 1)]);
+
+        data.try_push(None);
+        data.push(2);
+        assert_eq!(data.chunks.len(), 2);
+        assert_eq!(runs(&data.chunks[1]), vec![(2, 1)]);
+        assert_eq!(data.chunks[1].start_offset, 4);
+
+        assert_eq!(data.length(), 5);
+        assert_eq!(data.num_elements(), 4);
+        assert_eq!(data.num_runs(), 3);
+    }
+
+    #[test]
+    fn rle_matches_chunked() {
+        let mut rle = RleChunkedData::default();
+        let mut plain = ChunkedData::default();
+        test_populate(&mut rle, &mut plain);
+
+        assert_eq!(rle.length(), plain.length());
+        assert_eq!(rle.num_elements(), plain.num_elements());
+        assert_eq!(rle.num_runs(), 6);
+        assert_eq!(rle.first(), plain.first());
+        assert_eq!(rle.last(), plain.last());
+
+        assert_eq!(rle.iter().len(), plain.iter().len());
+        assert_eq!(
+            rle.iter_with_index().collect::<Vec<_>>(),
+            plain.iter_with_index().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            rle.iter_with_index().rev().collect::<Vec<_>>(),
+            plain.iter_with_index().rev().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn rle_prune() {
+        for index in 0..POPULATION.len() {
+            let mut rle = RleChunkedData::default();
+            let mut plain = ChunkedData::default();
+            test_populate(&mut rle, &mut plain);
+
+            assert!(rle.prune(index).is_ok());
+
+            let expected = POPULATION
+                .into_iter()
+                .skip(index + 1)
+                .enumerate()
+                .filter_map(|(a, b)| b.map(|b| (a, b)))
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                rle.iter_with_index()
+                    .map(|(a, b)| (a, *b))
+                    .collect::<Vec<_>>(),
+                expected,
+                "pruning up to {index} should match"
+            );
+            assert_eq!(rle.length(), POPULATION.len() - index - 1);
+            assert_eq!(rle.num_elements(), expected.len());
+        }
+    }
+
+    /// Pruning in the middle of a run should split it.
+    #[test]
+    fn rle_prune_splits_run() {
+        let mut data = RleChunkedData::default();
+        for _ in 0..5 {
+            data.push(1);
+        }
+        data.push(2);
+
+        assert!(data.prune(2).is_ok());
+        assert_eq!(runs(&data.chunks[0]), vec![(1, 2), (2, 1)]);
+        assert_eq!(data.num_elements(), 3);
+
+        // Pushing a repeated value after pruning should still extend the run.
+        data.push(2);
+        assert_eq!(runs(&data.chunks[0]), vec![(1, 2), (2, 2)]);
+        assert_eq!(data.length(), 4);
+    }
+
+    #[test]
+    fn rle_prune_everything() {
+        let mut data = RleChunkedData::default();
+        data.push(1);
+        data.push(1);
+
+        assert!(data.prune(1).is_ok());
+        assert!(data.no_elements());
+        assert_eq!(data.length(), 0);
+
+        data.push(1);
+        assert_eq!(data.iter_with_index().collect::<Vec<_>>(), vec![(0, &1)]);
+    }
+
+    #[test]
+    fn rle_prune_invalid() {
+        let mut data = RleChunkedData::<u64>::default();
+        assert_eq!(data.prune(0), Err(PruneError::Empty));
+
+        data.push(1);
+        assert_eq!(
+            data.prune(1),
+            Err(PruneError::IndexOutOfBounds {
+                requested: 1,
+                max: 0
+            })
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Storage backends for the values held within a single chunk of a
+//! [`crate::data::ChunkedData`].
+
+use std::ops::Range;
+
+/// A backend used to store the values of a single chunk.
+///
+/// By default, chunks are backed by a [`Vec`]. If the `smallvec` feature is
+/// enabled, a `SmallVec` can also be used, which keeps short chunks inline
+/// rather than allocating them on the heap.
+pub trait ChunkStorage<T>: Default {
+    /// Create the storage from a [`Vec`], ideally without copying.
+    fn from_vec(items: Vec<T>) -> Self;
+
+    /// Append an item to the end of the storage.
+    fn push(&mut self, item: T);
+
+    /// Insert an item at `index`, shifting all items after it.
+    fn insert(&mut self, index: usize, item: T);
+
+    /// Append all items from a slice to the end of the storage.
+    fn extend_from_slice(&mut self, items: &[T])
+    where
+        T: Clone;
+
+    /// Append all items from a slice of [`Copy`] items to the end of the storage. By
+    /// default, this is the same as [`ChunkStorage::extend_from_slice`], but backends can
+    /// override it to copy the items in bulk.
+    fn copy_from_slice(&mut self, items: &[T])
+    where
+        T: Copy,
+    {
+        self.extend_from_slice(items);
+    }
+
+    /// Append all items from an iterator to the end of the storage.
+    fn extend_from_iter<I: IntoIterator<Item = T>>(&mut self, items: I);
+
+    /// Reserve capacity for at least `additional` more items.
+    fn reserve(&mut self, additional: usize);
+
+    /// Reserve capacity for exactly `additional` more items, if possible.
+    fn reserve_exact(&mut self, additional: usize);
+
+    /// Return how many items can be stored without reallocating.
+    fn capacity(&self) -> usize;
+
+    /// Return how many items the storage has allocated space for on the heap. By
+    /// default, this is the same as [`ChunkStorage::capacity`].
+    fn heap_capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    /// Remove the first `count` items from the storage.
+    fn drain_front(&mut self, count: usize);
+
+    /// Remove the items within `range` from the storage, returning them.
+    fn drain_range(&mut self, range: Range<usize>) -> Vec<T>;
+
+    /// Return the stored items as a slice.
+    fn as_slice(&self) -> &[T];
+
+    /// Return the stored items as a mutable slice.
+    fn as_mut_slice(&mut self) -> &mut [T];
+
+    /// Return the number of stored items.
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    /// Return whether there are no stored items.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all items, keeping the allocated capacity.
+    fn clear(&mut self);
+
+    /// Shrink the backing storage as much as possible.
+    fn shrink_to_fit(&mut self);
+}
+
+impl<T> ChunkStorage<T> for Vec<T> {
+    fn from_vec(items: Vec<T>) -> Self {
+        items
+    }
+
+    fn push(&mut self, item: T) {
+        Vec::push(self, item);
+    }
+
+    fn insert(&mut self, index: usize, item: T) {
+        Vec::insert(self, index, item);
+    }
+
+    fn extend_from_slice(&mut self, items: &[T])
+    where
+        T: Clone,
+    {
+        Vec::extend_from_slice(self, items);
+    }
+
+    fn extend_from_iter<I: IntoIterator<Item = T>>(&mut self, items: I) {
+        Extend::extend(self, items);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional);
+    }
+
+    fn reserve_exact(&mut self, additional: usize) {
+        Vec::reserve_exact(self, additional);
+    }
+
+    fn capacity(&self) -> usize {
+        Vec::capacity(self)
+    }
+
+    fn drain_front(&mut self, count: usize) {
+        self.drain(..count);
+    }
+
+    fn drain_range(&mut self, range: Range<usize>) -> Vec<T> {
+        self.drain(range).collect()
+    }
+
+    fn as_slice(&self) -> &[T] {
+        Vec::as_slice(self)
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        Vec::as_mut_slice(self)
+    }
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn clear(&mut self) {
+        Vec::clear(self);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        Vec::shrink_to_fit(self);
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> ChunkStorage<A::Item> for smallvec::SmallVec<A> {
+    fn from_vec(items: Vec<A::Item>) -> Self {
+        smallvec::SmallVec::from_vec(items)
+    }
+
+    fn push(&mut self, item: A::Item) {
+        smallvec::SmallVec::push(self, item);
+    }
+
+    fn insert(&mut self, index: usize, item: A::Item) {
+        smallvec::SmallVec::insert(self, index, item);
+    }
+
+    fn extend_from_slice(&mut self, items: &[A::Item])
+    where
+        A::Item: Clone,
+    {
+        // `SmallVec::extend_from_slice` requires `Copy`.
+        Extend::extend(self, items.iter().cloned());
+    }
+
+    fn copy_from_slice(&mut self, items: &[A::Item])
+    where
+        A::Item: Copy,
+    {
+        smallvec::SmallVec::extend_from_slice(self, items);
+    }
+
+    fn extend_from_iter<I: IntoIterator<Item = A::Item>>(&mut self, items: I) {
+        Extend::extend(self, items);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        smallvec::SmallVec::reserve(self, additional);
+    }
+
+    fn reserve_exact(&mut self, additional: usize) {
+        smallvec::SmallVec::reserve_exact(self, additional);
+    }
+
+    fn capacity(&self) -> usize {
+        smallvec::SmallVec::capacity(self)
+    }
+
+    fn heap_capacity(&self) -> usize {
+        // Items stored inline don't take up any heap space.
+        if self.spilled() { self.capacity() } else { 0 }
+    }
+
+    fn drain_front(&mut self, count: usize) {
+        self.drain(..count);
+    }
+
+    fn drain_range(&mut self, range: Range<usize>) -> Vec<A::Item> {
+        self.drain(range).collect()
+    }
+
+    fn as_slice(&self) -> &[A::Item] {
+        smallvec::SmallVec::as_slice(self)
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [A::Item] {
+        smallvec::SmallVec::as_mut_slice(self)
+    }
+
+    fn len(&self) -> usize {
+        smallvec::SmallVec::len(self)
+    }
+
+    fn clear(&mut self) {
+        smallvec::SmallVec::clear(self);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        smallvec::SmallVec::shrink_to_fit(self);
+    }
+}
@@ -5,4 +5,5 @@
 //! change a lot. You've been warned.
 
 pub mod data;
+pub mod prelude;
 pub mod time;
@@ -0,0 +1,25 @@
+//! Re-exports of the commonly used types, so they can all be imported at once:
+//!
+//! ```
+//! use std::time::Instant;
+//!
+//! use timeless::prelude::*;
+//!
+//! let mut times = OffsetTimeList::default();
+//! let mut data = ChunkedData::<f64>::default();
+//!
+//! let index = times.add(Instant::now());
+//! data.push(1.0);
+//! assert_eq!(data.get(index), Some(&1.0));
+//! ```
+//!
+//! These are also still available from their own modules, e.g.
+//! [`crate::data::ChunkedData`].
+
+pub use crate::{
+    data::{
+        ChunkStorage, ChunkedCursor, ChunkedData, ChunkedDataIter, ChunkedDataView, ChunksMutIter,
+        DeltaChunkedData, DeltaEncodable, NonChunkedData, RleChunkedData, ToF64,
+    },
+    time::{OffsetTimeList, OffsetTimeListBuilder, OffsetTimeListIter},
+};
@@ -1 +1,2 @@
-// mod offset_time;
+mod offset_time;
+pub use offset_time::*;
@@ -2,14 +2,176 @@
 //! each a negative offset of the next value, with the latest
 //! value being represented in whole.
 
-use std::time::{Duration, Instant};
+use std::{
+    cmp::Ordering,
+    fmt,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+mod builder;
+pub use builder::*;
+
+/// An error returned when a list of times was expected to be in non-decreasing order,
+/// but was not.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeOrderError {
+    /// The position of the first time that was earlier than the one before it.
+    pub position: usize,
+}
+
+impl fmt::Display for TimeOrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "times are not in order; the time at position {} is earlier than the one before it",
+            self.position
+        )
+    }
+}
+
+impl std::error::Error for TimeOrderError {}
+
+/// An error returned by [`OffsetTimeList::from_unix_millis`] when the timestamps cannot
+/// be converted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnixMillisError {
+    /// The timestamps were not in non-decreasing order.
+    Unordered(TimeOrderError),
+
+    /// The timestamps cannot be represented as an [`Instant`]. This can happen on some
+    /// platforms if the oldest is from before the monotonic clock started, such as
+    /// before the system booted.
+    OutOfRange,
+}
+
+impl From<TimeOrderError> for UnixMillisError {
+    fn from(err: TimeOrderError) -> Self {
+        UnixMillisError::Unordered(err)
+    }
+}
+
+impl fmt::Display for UnixMillisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnixMillisError::Unordered(err) => err.fmt(f),
+            UnixMillisError::OutOfRange => {
+                write!(f, "the times cannot be represented as instants")
+            }
+        }
+    }
+}
+
+impl std::error::Error for UnixMillisError {}
+
+/// An error returned by [`OffsetTimeList::from_parts`] when the parts are invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimePartsError {
+    /// There were offsets, but no latest time for them to be relative to.
+    MissingCurrentTime,
+
+    /// The offsets add up to a span that is too large to represent, either as a
+    /// number of nanoseconds or as an [`Instant`] before the latest time.
+    SpanOverflow,
+}
+
+impl fmt::Display for TimePartsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimePartsError::MissingCurrentTime => {
+                write!(f, "there are offsets but no current time")
+            }
+            TimePartsError::SpanOverflow => {
+                write!(f, "the offsets add up to a span that is too large")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TimePartsError {}
+
+/// An error returned by [`OffsetTimeList::prune`] when nothing was pruned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimePruneError {
+    /// There are no stored times.
+    Empty,
+
+    /// There are no checkpoints to prune up to; see [`OffsetTimeList::checkpoint`].
+    NoCheckpoints,
+
+    /// No checkpoint was old enough to prune anything before it.
+    NothingToPrune,
+}
+
+impl fmt::Display for TimePruneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimePruneError::Empty => write!(f, "there are no stored times"),
+            TimePruneError::NoCheckpoints => write!(f, "there are no checkpoints"),
+            TimePruneError::NothingToPrune => {
+                write!(f, "no checkpoint was old enough to prune anything")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TimePruneError {}
+
+/// The result of pruning an [`OffsetTimeList`].
+///
+/// Both counts are in terms of stored times, i.e. indices, including the latest time.
+/// Pruning never removes the latest time, so `removed + remaining` is always the
+/// [`OffsetTimeList::len`] from before the prune, and `remaining` is at least 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PruneResult {
+    /// How many times were removed, which were at indices `0..removed` before pruning.
+    ///
+    /// Any corresponding [`crate::data::ChunkedData`] should have the same number of
+    /// entries removed, e.g. with `prune(removed - 1)` or `rebase(removed)`.
+    pub removed: usize,
+
+    /// How many times remain, including the latest time. This is the same as
+    /// [`OffsetTimeList::len`] after pruning.
+    pub remaining: usize,
+}
+
+/// Marks an offset in `time_offsets` that is too large for a [`u32`], and is stored in
+/// `wide_offsets` instead.
+const WIDE_OFFSET: u32 = u32::MAX;
 
 /// Time stored as a bunch of offsets.
+///
+/// Cloning an [`OffsetTimeList`] gives an independent copy that shares the same time
+/// coordinates as the original, as the latest time is stored as an [`Instant`]. Times in
+/// either can still be compared to each other, and to any other [`Instant`].
 #[derive(Default, Clone, Debug)]
 pub struct OffsetTimeList {
     time_offsets: Vec<u32>,
+
+    /// Offsets of at least [`WIDE_OFFSET`] milliseconds, which are marked in
+    /// `time_offsets` with [`WIDE_OFFSET`]. These are in the same order as their
+    /// markers, so the `n`-th marker corresponds to the `n`-th entry.
+    ///
+    /// Gaps this large (over 49 days) are rare, e.g. after a long suspend, so they're
+    /// kept out of line rather than making every offset wider.
+    wide_offsets: Vec<u64>,
+
+    /// The sum of all offsets, in milliseconds.
+    total_offset: u64,
+
     checkpoints: Vec<(Instant, usize)>,
     current_time: Option<Instant>,
+
+    /// Automatically add a checkpoint once more than this many offsets were added
+    /// since the latest one. See [`OffsetTimeListBuilder::max_entries`].
+    max_entries: Option<usize>,
+
+    /// Automatically add a checkpoint once the latest one is at least this old.
+    /// See [`OffsetTimeListBuilder::checkpoint_interval`].
+    checkpoint_interval: Option<Duration>,
+
+    /// The most checkpoints to keep before thinning them out. See
+    /// [`OffsetTimeList::set_max_checkpoints`].
+    max_checkpoints: Option<usize>,
 }
 
 impl OffsetTimeList {
@@ -18,24 +180,40 @@ impl OffsetTimeList {
         Self::with_both_capacity(capacity, 0)
     }
 
+    /// Create a [`OffsetTimeList`] that automatically adds a checkpoint whenever a time
+    /// is added at least `interval` after the latest checkpoint, or after the oldest
+    /// time if there are no checkpoints. See [`OffsetTimeListBuilder::checkpoint_interval`].
+    pub fn with_checkpoint_interval(interval: Duration) -> Self {
+        Self::builder().checkpoint_interval(interval).build()
+    }
+
     /// Create a [`OffsetTimeList`] with both time storage and
     /// checkpoint capacity pre-initialized.
     pub fn with_both_capacity(time_capacity: usize, checkpoint_capacity: usize) -> Self {
         Self {
             time_offsets: Vec::with_capacity(time_capacity),
+            wide_offsets: Vec::new(),
+            total_offset: 0,
             checkpoints: Vec::with_capacity(checkpoint_capacity),
             current_time: None,
+            max_entries: None,
+            checkpoint_interval: None,
+            max_checkpoints: None,
         }
     }
 
-    /// Add a time entry. This will return the current index,
-    /// which can be used to update any [`crate::data::Data`] entries
-    /// that are corresponding to this [`OffsetTimeList`].
+    /// Add a time entry. This will return the index of the added time, which matches
+    /// the index returned alongside it by [`OffsetTimeList::iter_with_index`], and can be
+    /// used to update any [`crate::data::ChunkedData`] entries that are corresponding to
+    /// this [`OffsetTimeList`].
+    ///
+    /// If the list was built with a checkpoint policy (see [`OffsetTimeListBuilder`]),
+    /// this may also add a checkpoint at the added time.
     pub fn add(&mut self, time: Instant) -> usize {
-        if let Some(current_time) = self.current_time {
-            let offset = time.duration_since(current_time).as_millis() as u32;
+        let index = if let Some(current_time) = self.current_time {
+            let offset = time.duration_since(current_time).as_millis();
             self.current_time = Some(time);
-            self.time_offsets.push(offset);
+            self.push_offset(u64::try_from(offset).unwrap_or(u64::MAX));
 
             // The current "index" is the length of the vec - 1, but we
             // add back 1 since we store the current head as a separate instant.
@@ -43,51 +221,715 @@ impl OffsetTimeList {
         } else {
             self.current_time = Some(time);
 
-            1
+            0
+        };
+
+        if self.checkpoint_due(time) {
+            self.checkpoint();
+        }
+
+        index
+    }
+
+    /// Push an offset in milliseconds from the previous latest time to the new one.
+    fn push_offset(&mut self, offset: u64) {
+        match u32::try_from(offset) {
+            Ok(offset) if offset != WIDE_OFFSET => {
+                self.time_offsets.push(offset);
+            }
+            _ => {
+                self.time_offsets.push(WIDE_OFFSET);
+                self.wide_offsets.push(offset);
+            }
+        }
+
+        self.total_offset += offset;
+    }
+
+    /// Returns an iterator of the offsets in milliseconds, starting from the oldest, with
+    /// any wide offsets filled in.
+    fn offsets(&self) -> Offsets<'_> {
+        Offsets {
+            offsets: self.time_offsets.iter(),
+            wide_offsets: self.wide_offsets.iter(),
+        }
+    }
+
+    /// Return whether the checkpoint policy calls for a checkpoint at `time`, which
+    /// was just added.
+    fn checkpoint_due(&self, time: Instant) -> bool {
+        let latest = self.checkpoints.last();
+
+        let too_many_entries = self.max_entries.is_some_and(|max_entries| {
+            let since = self
+                .entries_since_checkpoint()
+                .unwrap_or(self.time_offsets.len());
+            since > max_entries
+        });
+        let too_old = self.checkpoint_interval.is_some_and(|interval| {
+            let since = match latest {
+                Some(&(instant, _)) => Some(instant),
+                None => self.first(),
+            };
+            since.is_some_and(|since| time.duration_since(since) >= interval)
+        });
+
+        too_many_entries || too_old
+    }
+
+    /// Return how many times are stored. This should match the length of any corresponding
+    /// [`crate::data::ChunkedData`] (see [`crate::data::ChunkedData::length`]).
+    ///
+    /// Note this is one more than the number of stored offsets, as the latest time is
+    /// stored separately; every other time is stored as an offset from the one after it.
+    pub fn len(&self) -> usize {
+        if self.current_time.is_some() {
+            self.time_offsets.len() + 1
+        } else {
+            0
         }
     }
 
+    /// Return whether there are no stored times.
+    pub fn is_empty(&self) -> bool {
+        self.current_time.is_none()
+    }
+
+    /// Return the time at `index`, or [`None`] if it is out of range. Indices start from
+    /// the oldest stored time at 0, matching the value returned by [`OffsetTimeList::add`]
+    /// and the indices of [`OffsetTimeList::iter_with_index`].
+    ///
+    /// This walks backwards from the latest time, so it is cheaper for recent times.
+    pub fn get(&self, index: usize) -> Option<Instant> {
+        let current_time = self.current_time?;
+        let after = self.time_offsets.len().checked_sub(index)?;
+        let total: u64 = self.offsets().rev().take(after).sum();
+
+        Some(current_time - Duration::from_millis(total))
+    }
+
+    /// Return the oldest stored time, or [`None`] if there are no stored times.
+    pub fn first(&self) -> Option<Instant> {
+        self.current_time
+            .map(|current_time| current_time - Duration::from_millis(self.total_offset))
+    }
+
+    /// Return the latest stored time, or [`None`] if there are no stored times.
+    pub fn last(&self) -> Option<Instant> {
+        self.current_time
+    }
+
     /// Add a "checkpoint"; this is used for pruning by time.
+    ///
+    /// This must be called after at least one time has been added with
+    /// [`OffsetTimeList::add`]. Otherwise, this panics in debug builds, and does
+    /// nothing in release builds.
+    ///
+    /// If there is a cap on the number of checkpoints (see
+    /// [`OffsetTimeList::set_max_checkpoints`]), this may drop an older checkpoint to
+    /// make room.
     pub fn checkpoint(&mut self) {
-        if let Some(current_time) = self.current_time {
-            self.checkpoints
-                .push((current_time, self.time_offsets.len()));
+        let Some(current_time) = self.current_time else {
+            debug_assert!(false, "checkpoint called before any add");
+            return;
+        };
+
+        self.checkpoints
+            .push((current_time, self.time_offsets.len()));
+        self.thin_checkpoints();
+    }
+
+    /// Cap the number of stored checkpoints to `max_checkpoints`, which is at least 1,
+    /// so that they don't grow without bound if [`OffsetTimeList::checkpoint`] is called
+    /// much more often than [`OffsetTimeList::prune`].
+    ///
+    /// Whenever there would be more checkpoints than the cap, the checkpoint whose
+    /// removal leaves the smallest gap in time is dropped. The oldest and newest
+    /// checkpoints are always kept (only the newest if the cap is 1), so the
+    /// checkpoints stay spread out over the stored times, and pruning by age gets less
+    /// precise rather than being unable to prune older or newer times.
+    pub fn set_max_checkpoints(&mut self, max_checkpoints: usize) {
+        self.max_checkpoints = Some(std::cmp::max(max_checkpoints, 1));
+        self.thin_checkpoints();
+    }
+
+    /// Drop checkpoints until they fit within `max_checkpoints`, if set.
+    fn thin_checkpoints(&mut self) {
+        let Some(max_checkpoints) = self.max_checkpoints else {
+            return;
+        };
+
+        while self.checkpoints.len() > max_checkpoints {
+            if max_checkpoints == 1 {
+                let newest = self.checkpoints.len() - 1;
+                self.checkpoints.drain(..newest);
+                break;
+            }
+
+            // Drop the checkpoint with the closest neighbours, other than the oldest
+            // or newest.
+            let closest = (1..self.checkpoints.len() - 1)
+                .min_by_key(|&i| {
+                    let (before, after) = (self.checkpoints[i - 1].0, self.checkpoints[i + 1].0);
+                    after.saturating_duration_since(before)
+                })
+                .expect("there are at least 3 checkpoints");
+            self.checkpoints.remove(closest);
         }
     }
 
+    /// Return how many checkpoints are currently stored.
+    pub fn checkpoint_count(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    /// Return how many times were added since the latest checkpoint, or [`None`] if
+    /// there are no checkpoints.
+    ///
+    /// If the latest checkpoint is used by [`OffsetTimeList::prune`], every time before
+    /// it is removed, so `len() - 1 - entries_since_checkpoint()` times would be removed.
+    pub fn entries_since_checkpoint(&self) -> Option<usize> {
+        let &(_, index) = self.checkpoints.last()?;
+
+        Some(self.time_offsets.len() - index)
+    }
+
+    /// Discard all but the latest `keep_last` checkpoints, without changing any
+    /// stored times.
+    ///
+    /// This can be used to bound how many checkpoints are kept if
+    /// [`OffsetTimeList::checkpoint`] is called much more often than
+    /// [`OffsetTimeList::prune`]. Note that this may make later prunes less precise.
+    pub fn clear_old_checkpoints(&mut self, keep_last: usize) {
+        let to_remove = self.checkpoints.len().saturating_sub(keep_last);
+        self.checkpoints.drain(..to_remove);
+    }
+
     /// Approximately prune time values older than the given [`Duration`],
-    /// and returns the new index.
-    pub fn prune(&mut self, max_age: Duration) -> Option<usize> {
-        if let Some(current_time) = self.current_time {
-            let checkpoint_index = match self.checkpoints.binary_search_by(|(instant, _)| {
-                println!(
-                    "current time duration since: {:?}",
-                    current_time.duration_since(*instant)
-                );
-                current_time.duration_since(*instant).cmp(&max_age)
-            }) {
-                Ok(index) | Err(index) => index,
+    /// returning how many times were removed and how many remain.
+    ///
+    /// This removes every time before the latest checkpoint that is at least `max_age`
+    /// old, so how much is pruned depends on how often [`OffsetTimeList::checkpoint`]
+    /// was called. If nothing was removed, a [`TimePruneError`] is returned with the
+    /// reason why.
+    ///
+    /// If anything was removed, the same number of entries should be pruned from
+    /// any corresponding [`crate::data::ChunkedData`], e.g. by pruning up to
+    /// `removed - 1`.
+    pub fn prune(&mut self, max_age: Duration) -> Result<PruneResult, TimePruneError> {
+        let current_time = self.current_time.ok_or(TimePruneError::Empty)?;
+        if self.checkpoints.is_empty() {
+            return Err(TimePruneError::NoCheckpoints);
+        }
+
+        // Checkpoints are stored in ascending order of time, so their ages are in
+        // descending order. This means every checkpoint at least `max_age` old comes
+        // before every younger one, and we can find the boundary between the two.
+        let expired = self.checkpoints.partition_point(|(instant, _)| {
+            match current_time.duration_since(*instant).cmp(&max_age) {
+                // Older than `max_age`, so everything before it can be pruned.
+                Ordering::Greater => true,
+                // Exactly `max_age` old. This time isn't older than `max_age` but
+                // everything before it is, so it can also be used.
+                Ordering::Equal => true,
+                // Younger than `max_age`; this and all later checkpoints are kept.
+                Ordering::Less => false,
+            }
+        });
+
+        // Only the youngest expired checkpoint matters; every time before it is older
+        // than `max_age`.
+        let (_, index) = self
+            .checkpoints
+            .drain(..expired)
+            .next_back()
+            .ok_or(TimePruneError::NothingToPrune)?;
+        let removed = std::cmp::min(index, self.time_offsets.len());
+        if removed == 0 {
+            return Err(TimePruneError::NothingToPrune);
+        }
+
+        self.remove_oldest(removed);
+
+        Ok(PruneResult {
+            removed,
+            remaining: self.time_offsets.len() + 1,
+        })
+    }
+
+    /// Prune the oldest times until at most `count` remain, returning how many were
+    /// removed. Unlike [`OffsetTimeList::prune`], this doesn't need any checkpoints.
+    ///
+    /// Any checkpoints for the removed times are also removed. If anything was removed,
+    /// the same number of entries should be pruned from any corresponding
+    /// [`crate::data::ChunkedData`], like with [`OffsetTimeList::prune`].
+    pub fn prune_to_count(&mut self, count: usize) -> usize {
+        let length = self.len();
+        let removed = length.saturating_sub(count);
+
+        if removed == length {
+            self.reset();
+        } else if removed > 0 {
+            self.remove_oldest(removed);
+        }
+
+        removed
+    }
+
+    /// Remove the oldest `removed` times, which must be fewer than the number of stored
+    /// times, along with any checkpoints for them.
+    fn remove_oldest(&mut self, removed: usize) {
+        self.total_offset -= self.offsets().take(removed).sum::<u64>();
+
+        let wide = self.time_offsets[..removed]
+            .iter()
+            .filter(|&&offset| offset == WIDE_OFFSET)
+            .count();
+        self.time_offsets.drain(..removed);
+        self.wide_offsets.drain(..wide);
+
+        // The remaining checkpoints all come after the removed times, so they just
+        // need to be shifted down to match.
+        self.checkpoints.retain_mut(|(_, index)| {
+            let Some(rebased) = index.checked_sub(removed) else {
+                return false;
             };
+            *index = rebased;
+
+            true
+        });
+    }
+
+    /// Prune every stored time and checkpoint, regardless of whether any checkpoints
+    /// were added. Returns how many times were stored beforehand, which can be used to
+    /// prune any corresponding [`crate::data::ChunkedData`], or [`None`] if there
+    /// were no stored times.
+    pub fn prune_all(&mut self) -> Option<usize> {
+        self.current_time?;
+
+        let length = self.len();
+        self.reset();
+
+        Some(length)
+    }
+
+    /// Clear all stored times and checkpoints, while keeping the allocated capacity, like
+    /// [`Vec::clear`]. To also release the allocated memory, use
+    /// [`OffsetTimeList::reset_and_shrink`].
+    pub fn reset(&mut self) {
+        self.time_offsets.clear();
+        self.wide_offsets.clear();
+        self.total_offset = 0;
+        self.checkpoints.clear();
+        self.current_time = None;
+    }
+
+    /// Clear all stored times and checkpoints, and shrink the allocated capacity.
+    pub fn reset_and_shrink(&mut self) {
+        self.reset();
+        self.shrink_to_fit();
+    }
+
+    /// Shrink the allocated capacity for both times and checkpoints as much as possible,
+    /// such as after a large prune.
+    pub fn shrink_to_fit(&mut self) {
+        self.time_offsets.shrink_to_fit();
+        self.wide_offsets.shrink_to_fit();
+        self.checkpoints.shrink_to_fit();
+    }
+
+    /// Return how many times and how many checkpoints can be stored without
+    /// reallocating, respectively.
+    ///
+    /// As the latest time is stored separately, the capacity for times doesn't count
+    /// it, and so is comparable to one less than [`OffsetTimeList::len`].
+    pub fn capacity(&self) -> (usize, usize) {
+        (self.time_offsets.capacity(), self.checkpoints.capacity())
+    }
+
+    /// Return the oldest and latest stored times, or [`None`] if there are no stored times.
+    pub fn time_range(&self) -> Option<(Instant, Instant)> {
+        let current_time = self.current_time?;
+
+        Some((
+            current_time - Duration::from_millis(self.total_offset),
+            current_time,
+        ))
+    }
 
-            let checkpoint_index =
-                std::cmp::min(checkpoint_index, self.checkpoints.len().saturating_sub(1));
-
-            match self.checkpoints.drain(..checkpoint_index).last() {
-                Some((_, index)) => {
-                    if index < self.time_offsets.len() {
-                        self.time_offsets.drain(..index);
-                        Some(self.time_offsets.len())
-                    } else {
-                        self.time_offsets.clear();
-                        self.current_time = None;
-                        Some(0)
-                    }
-                }
-                None => None,
+    /// Return the total time covered by the stored times, i.e. the time between the
+    /// oldest and latest stored times, or [`None`] if there are no stored times.
+    ///
+    /// This is kept as the times are added and pruned, so it is cheap to call.
+    pub fn total_span(&self) -> Option<Duration> {
+        self.current_time?;
+
+        Some(Duration::from_millis(self.total_offset))
+    }
+
+    /// Return the mean interval between consecutive stored times, or [`None`] if
+    /// there are fewer than two stored times.
+    pub fn average_interval(&self) -> Option<Duration> {
+        if self.time_offsets.is_empty() {
+            return None;
+        }
+
+        Some(Duration::from_millis(self.total_offset).div_f64(self.time_offsets.len() as f64))
+    }
+
+    /// Return the (population) standard deviation of the intervals between consecutive
+    /// stored times, or [`None`] if there are fewer than two stored times.
+    pub fn interval_std_dev(&self) -> Option<Duration> {
+        if self.time_offsets.is_empty() {
+            return None;
+        }
+
+        let (sum, sum_of_squares) =
+            self.offsets()
+                .fold((0.0, 0.0), |(sum, sum_of_squares), offset| {
+                    let offset = offset as f64;
+                    (sum + offset, sum_of_squares + offset * offset)
+                });
+
+        let count = self.time_offsets.len() as f64;
+        let mean = sum / count;
+        let variance = (sum_of_squares / count - mean * mean).max(0.0);
+
+        Some(Duration::from_secs_f64(variance.sqrt() / 1000.0))
+    }
+
+    /// Build an [`OffsetTimeList`] from its parts, like those returned by
+    /// [`OffsetTimeList::into_parts`]: the latest time, and the offset in milliseconds
+    /// from each older time to the one after it. No checkpoints are added.
+    ///
+    /// If there are offsets but no latest time, or the offsets add up to a span that
+    /// doesn't fit in a [`u64`] of nanoseconds or goes before the earliest
+    /// representable [`Instant`], a [`TimePartsError`] is returned.
+    pub fn from_parts(
+        current_time: Option<Instant>, time_offsets: Vec<u64>,
+    ) -> Result<Self, TimePartsError> {
+        let Some(current_time) = current_time else {
+            if !time_offsets.is_empty() {
+                return Err(TimePartsError::MissingCurrentTime);
+            }
+
+            return Ok(Self::default());
+        };
+
+        let total_offset = time_offsets
+            .iter()
+            .try_fold(0u64, |total, &offset| total.checked_add(offset))
+            .filter(|&total| span_fits(current_time, total))
+            .ok_or(TimePartsError::SpanOverflow)?;
+
+        let mut times = Self::with_capacity(time_offsets.len());
+        times.current_time = Some(current_time);
+        for offset in time_offsets {
+            times.push_offset(offset);
+        }
+        debug_assert_eq!(times.total_offset, total_offset);
+
+        Ok(times)
+    }
+
+    /// Split this into the latest time and the offsets of the older times in milliseconds,
+    /// dropping any checkpoints. This is the inverse of [`OffsetTimeList::from_parts`],
+    /// and the offsets are the same as [`OffsetTimeList::iter_offsets`].
+    pub fn into_parts(self) -> (Option<Instant>, Vec<u64>) {
+        (self.current_time, self.offsets().collect())
+    }
+
+    /// Return every stored time as UNIX milliseconds, from oldest to latest.
+    ///
+    /// Since an [`Instant`] has no relation to the wall clock on its own, `epoch` is
+    /// the [`SystemTime`] corresponding to the latest stored time; every other time
+    /// is placed relative to it. Times before the UNIX epoch are clamped to 0, and
+    /// `epoch` is rounded down to whole milliseconds.
+    pub fn to_unix_millis(&self, epoch: SystemTime) -> Vec<u64> {
+        if self.current_time.is_none() {
+            return Vec::new();
+        }
+
+        let latest = epoch
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_millis() as u64)
+            .unwrap_or(0);
+
+        let mut millis = Vec::with_capacity(self.time_offsets.len() + 1);
+        millis.push(latest);
+
+        let mut time = latest;
+        for offset in self.offsets().rev() {
+            time = time.saturating_sub(offset);
+            millis.push(time);
+        }
+
+        millis.reverse();
+        millis
+    }
+
+    /// Create a [`OffsetTimeList`] from a list of UNIX millisecond timestamps, which must
+    /// be in non-decreasing order. Otherwise, [`UnixMillisError::Unordered`] is returned.
+    ///
+    /// The times are mapped to an [`Instant`] by comparing against the current
+    /// [`SystemTime`], so they are only accurate to the millisecond. No checkpoints are
+    /// added. If any of the times cannot be represented as an [`Instant`],
+    /// [`UnixMillisError::OutOfRange`] is returned rather than shifting the times.
+    pub fn from_unix_millis(millis: &[u64]) -> Result<Self, UnixMillisError> {
+        if let Some(position) = millis.windows(2).position(|w| w[0] > w[1]) {
+            return Err(TimeOrderError {
+                position: position + 1,
             }
+            .into());
+        }
+
+        let (Some(&first), Some(&latest)) = (millis.first(), millis.last()) else {
+            return Ok(Self::default());
+        };
+
+        let now = Instant::now();
+        let latest_time = UNIX_EPOCH
+            .checked_add(Duration::from_millis(latest))
+            .ok_or(UnixMillisError::OutOfRange)?;
+        let since = SystemTime::now()
+            .duration_since(latest_time)
+            .map_err(|err| err.duration());
+        let current_time = instant_since(now, since)
+            .filter(|&current_time| span_fits(current_time, latest - first))
+            .ok_or(UnixMillisError::OutOfRange)?;
+
+        let mut times = Self::with_capacity(millis.len() - 1);
+        times.current_time = Some(current_time);
+        for w in millis.windows(2) {
+            times.push_offset(w[1] - w[0]);
+        }
+
+        Ok(times)
+    }
+
+    /// Return the raw stored offsets in milliseconds, starting from the oldest.
+    ///
+    /// Each offset is the gap from a time to the _next_ time, so the offset at index `i`
+    /// is the gap between the times at index `i` and `i + 1`. There is no offset for the
+    /// latest time, so this has one less element than [`OffsetTimeList::len`], or none
+    /// if there are no stored times.
+    ///
+    /// Offsets of [`u32::MAX`] milliseconds or more (over 49 days) are stored separately,
+    /// and show up here as [`u32::MAX`]. [`OffsetTimeList::iter_offsets`] returns the
+    /// actual values.
+    pub fn as_offsets(&self) -> &[u32] {
+        &self.time_offsets
+    }
+
+    /// Returns an iterator of each stored offset as a [`Duration`]. See
+    /// [`OffsetTimeList::as_offsets`].
+    pub fn iter_offsets(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Duration> + ExactSizeIterator + '_ {
+        self.offsets().map(Duration::from_millis)
+    }
+
+    /// Returns an iterator of each stored time alongside the raw offset it was stored
+    /// with, in milliseconds since the previous time, starting from the oldest. The oldest
+    /// time has nothing before it, so its offset is 0.
+    ///
+    /// This can be used to inspect how the times are distributed, such as for jitter.
+    pub fn iter_with_offsets(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (u64, Instant)> + ExactSizeIterator {
+        let offsets = LeadingZero {
+            zero: self.current_time.is_some(),
+            offsets: self.offsets(),
+        };
+
+        offsets
+            .zip(self.iter_with_index())
+            .map(|(offset, (_, time))| (offset, time))
+    }
+
+    /// An alias of [`OffsetTimeList::iter_with_index`].
+    #[inline]
+    pub fn iter(&self) -> OffsetTimeListIter<'_> {
+        self.iter_with_index()
+    }
+
+    /// Returns an iterator of times alongside the associated indices for each time,
+    /// starting from the oldest stored time at index 0.
+    ///
+    /// These indices line up with those of any [`crate::data::ChunkedData`] that is
+    /// populated alongside this [`OffsetTimeList`].
+    pub fn iter_with_index(&self) -> OffsetTimeListIter<'_> {
+        match self.current_time {
+            Some(current_time) => OffsetTimeListIter {
+                offsets: self.offsets(),
+                front: current_time - Duration::from_millis(self.total_offset),
+                back: current_time,
+                front_index: 0,
+                remaining: self.time_offsets.len() + 1,
+            },
+            None => OffsetTimeListIter {
+                offsets: self.offsets(),
+                front: Instant::now(),
+                back: Instant::now(),
+                front_index: 0,
+                remaining: 0,
+            },
+        }
+    }
+}
+
+/// Return the [`Instant`] that is `since` before `now`, or after it if `since` is an
+/// error, if it can be represented.
+fn instant_since(now: Instant, since: Result<Duration, Duration>) -> Option<Instant> {
+    match since {
+        Ok(ago) => now.checked_sub(ago),
+        Err(ahead) => now.checked_add(ahead),
+    }
+}
+
+/// Return whether a span of `total_offset` milliseconds ending at `current_time` can be
+/// represented, both as a [`u64`] of nanoseconds and as an [`Instant`] for its start.
+fn span_fits(current_time: Instant, total_offset: u64) -> bool {
+    total_offset.checked_mul(1_000_000).is_some()
+        && current_time
+            .checked_sub(Duration::from_millis(total_offset))
+            .is_some()
+}
+
+/// Two [`OffsetTimeList`]s are equal if they have the same stored times, i.e. the same
+/// offsets and the same latest time. Checkpoints and the checkpoint policy don't affect
+/// equality.
+impl PartialEq for OffsetTimeList {
+    fn eq(&self, other: &Self) -> bool {
+        self.current_time == other.current_time
+            && self.time_offsets == other.time_offsets
+            && self.wide_offsets == other.wide_offsets
+    }
+}
+
+impl Eq for OffsetTimeList {}
+
+/// An iterator of the offsets in milliseconds of an [`OffsetTimeList`], with any wide
+/// offsets filled in.
+#[derive(Clone)]
+struct Offsets<'a> {
+    offsets: std::slice::Iter<'a, u32>,
+
+    /// The wide offsets that have not been reached yet, which line up with the
+    /// remaining [`WIDE_OFFSET`] markers in `offsets`.
+    wide_offsets: std::slice::Iter<'a, u64>,
+}
+
+impl Iterator for Offsets<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match *self.offsets.next()? {
+            WIDE_OFFSET => self.wide_offsets.next().copied(),
+            offset => Some(offset.into()),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.offsets.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Offsets<'_> {}
+
+impl DoubleEndedIterator for Offsets<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match *self.offsets.next_back()? {
+            WIDE_OFFSET => self.wide_offsets.next_back().copied(),
+            offset => Some(offset.into()),
+        }
+    }
+}
+
+/// Like [`Offsets`], but starting with an offset of 0 for the oldest time if `zero`
+/// is set.
+struct LeadingZero<'a> {
+    zero: bool,
+    offsets: Offsets<'a>,
+}
+
+impl Iterator for LeadingZero<'_> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if std::mem::take(&mut self.zero) {
+            Some(0)
         } else {
-            None
+            self.offsets.next()
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.offsets.len() + usize::from(self.zero);
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for LeadingZero<'_> {}
+
+impl DoubleEndedIterator for LeadingZero<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.offsets
+            .next_back()
+            .or_else(|| std::mem::take(&mut self.zero).then_some(0))
+    }
+}
+
+/// An iterator of `(usize, Instant)` pairs created from an [`OffsetTimeList`].
+pub struct OffsetTimeListIter<'a> {
+    /// The offsets that have not been stepped over yet.
+    offsets: Offsets<'a>,
+    front: Instant,
+    back: Instant,
+    front_index: usize,
+    remaining: usize,
+}
+
+impl Iterator for OffsetTimeListIter<'_> {
+    type Item = (usize, Instant);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let item = (self.front_index, self.front);
+
+        if let Some(offset) = self.offsets.next() {
+            self.front += Duration::from_millis(offset);
         }
+        self.front_index += 1;
+        self.remaining -= 1;
+
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for OffsetTimeListIter<'_> {}
+
+impl DoubleEndedIterator for OffsetTimeListIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let item = (self.front_index + self.remaining - 1, self.back);
+
+        if let Some(offset) = self.offsets.next_back() {
+            self.back -= Duration::from_millis(offset);
+        }
+        self.remaining -= 1;
+
+        Some(item)
     }
 }
 
@@ -95,6 +937,16 @@ impl OffsetTimeList {
 mod tests {
     use super::*;
 
+    /// Return the current time in UNIX milliseconds. Timestamps for
+    /// [`OffsetTimeList::from_unix_millis`] are built from this rather than from the
+    /// 1970s, as those may be before [`Instant`] can go on some platforms.
+    fn unix_millis_now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64
+    }
+
     #[test]
     fn test_add() {
         let mut times = OffsetTimeList::default();
@@ -112,23 +964,1068 @@ mod tests {
     }
 
     #[test]
-    fn test_prune() {
+    #[cfg_attr(
+        debug_assertions,
+        should_panic(expected = "checkpoint called before any add")
+    )]
+    fn test_checkpoint_before_add() {
         let mut times = OffsetTimeList::default();
+        times.checkpoint();
 
-        // Test fully empty.
-        assert_eq!(times.prune(Duration::from_secs(0)), None);
+        assert!(times.checkpoints.is_empty());
+    }
 
-        let now = Instant::now();
-        times.add(now);
+    #[test]
+    fn test_entries_since_checkpoint() {
+        let mut times = OffsetTimeList::default();
+        assert_eq!(times.entries_since_checkpoint(), None);
 
-        // Test no checkpoint.
-        assert_eq!(times.prune(Duration::from_secs(0)), None);
+        let now = Instant::now();
+        for i in 0..5 {
+            times.add(now + Duration::from_secs(i));
+        }
+        assert_eq!(times.entries_since_checkpoint(), None);
 
-        // Add a checkpoint, try clearing it.
-        times.add(now);
         times.checkpoint();
+        assert_eq!(times.entries_since_checkpoint(), Some(0));
+
+        for i in 5..8 {
+            times.add(now + Duration::from_secs(i));
+        }
+        assert_eq!(times.entries_since_checkpoint(), Some(3));
 
-        assert_eq!(times.prune(Duration::from_secs(1000)), Some(1));
-        assert_eq!(times.prune(Duration::from_secs(0)), None);
+        // Pruning up to the checkpoint removes everything before it.
+        let expected_removed = times.len() - 1 - times.entries_since_checkpoint().unwrap();
+        let result = times.prune(Duration::ZERO).unwrap();
+        assert_eq!(result.removed, expected_removed);
+        assert_eq!(times.entries_since_checkpoint(), None);
+    }
+
+    #[test]
+    fn test_clear_old_checkpoints() {
+        let mut times = OffsetTimeList::default();
+        assert_eq!(times.checkpoint_count(), 0);
+        times.clear_old_checkpoints(2);
+        assert_eq!(times.checkpoint_count(), 0);
+
+        let now = Instant::now();
+        for i in 0..5 {
+            times.add(now + Duration::from_secs(i));
+            times.checkpoint();
+        }
+        assert_eq!(times.checkpoint_count(), 5);
+
+        times.clear_old_checkpoints(10);
+        assert_eq!(times.checkpoint_count(), 5);
+
+        times.clear_old_checkpoints(2);
+        assert_eq!(times.checkpoint_count(), 2);
+        assert_eq!(
+            times.checkpoints,
+            vec![
+                (now + Duration::from_secs(3), 3),
+                (now + Duration::from_secs(4), 4)
+            ]
+        );
+
+        // Times are untouched.
+        assert_eq!(times.time_offsets, vec![1000; 4]);
+        assert_eq!(
+            times.time_range(),
+            Some((now, now + Duration::from_secs(4)))
+        );
+
+        times.clear_old_checkpoints(0);
+        assert_eq!(times.checkpoint_count(), 0);
+        assert_eq!(times.time_offsets.len(), 4);
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut times = OffsetTimeList::default();
+        let now = Instant::now();
+        times.add(now);
+        times.add(now + Duration::from_secs(1));
+        times.checkpoint();
+
+        let mut cloned = times.clone();
+        assert_eq!(cloned.time_range(), times.time_range());
+
+        // Changing one doesn't affect the other.
+        times.add(now + Duration::from_secs(2));
+        cloned.add(now + Duration::from_secs(5));
+        cloned.checkpoint();
+
+        assert_eq!(
+            times.time_range(),
+            Some((now, now + Duration::from_secs(2)))
+        );
+        assert_eq!(
+            cloned.time_range(),
+            Some((now, now + Duration::from_secs(5)))
+        );
+        assert_eq!(times.time_offsets, vec![1000, 1000]);
+        assert_eq!(cloned.time_offsets, vec![1000, 4000]);
+        assert_eq!(times.checkpoint_count(), 1);
+        assert_eq!(cloned.checkpoint_count(), 2);
+    }
+
+    #[test]
+    fn test_eq() {
+        let now = Instant::now();
+        let mut a = OffsetTimeList::default();
+        let mut b = OffsetTimeList::with_both_capacity(10, 10);
+        assert_eq!(a, b);
+
+        for i in 0..3 {
+            a.add(now + Duration::from_secs(i));
+            b.add(now + Duration::from_secs(i));
+        }
+        assert_eq!(a, b);
+
+        // Checkpoints don't matter.
+        a.checkpoint();
+        assert_eq!(a, b);
+
+        // The same offsets with a different latest time aren't equal.
+        let mut c = OffsetTimeList::default();
+        for i in 1..4 {
+            c.add(now + Duration::from_secs(i));
+        }
+        assert_eq!(c.time_offsets, a.time_offsets);
+        assert_ne!(a, c);
+
+        b.add(now + Duration::from_secs(3));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_len() {
+        let mut times = OffsetTimeList::default();
+        assert_eq!(times.len(), 0);
+        assert!(times.is_empty());
+
+        // The first time has no offset.
+        let now = Instant::now();
+        assert_eq!(times.add(now), 0);
+        assert_eq!(times.len(), 1);
+        assert!(times.time_offsets.is_empty());
+        assert!(!times.is_empty());
+
+        for i in 1..5 {
+            let index = times.add(now + Duration::from_secs(i));
+            assert_eq!(times.len(), i as usize + 1);
+            assert_eq!(times.len(), times.time_offsets.len() + 1);
+            assert_eq!(times.len(), times.iter_with_index().len());
+            assert_eq!(index, times.len() - 1);
+        }
+        times.checkpoint();
+
+        // Pruning down to the latest time.
+        let result = times.prune(Duration::ZERO).unwrap();
+        assert_eq!(
+            result,
+            PruneResult {
+                removed: 4,
+                remaining: 1
+            }
+        );
+        assert_eq!(result.remaining, times.len());
+        assert_eq!(times.len(), 1);
+        assert!(!times.is_empty());
+
+        // Pruning everything.
+        assert_eq!(times.prune_all(), Some(1));
+        assert_eq!(times.len(), 0);
+        assert!(times.is_empty());
+
+        times.add(now);
+        times.reset();
+        assert_eq!(times.len(), 0);
+        assert!(times.is_empty());
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut times = OffsetTimeList::with_both_capacity(10, 10);
+
+        let now = Instant::now();
+        for i in 0..5 {
+            times.add(now + Duration::from_secs(i));
+            times.checkpoint();
+        }
+
+        times.reset();
+        assert_eq!(times.len(), 0);
+        assert!(times.time_offsets.is_empty());
+        assert!(times.checkpoints.is_empty());
+        assert_eq!(times.current_time, None);
+        assert_eq!(times.time_offsets.capacity(), 10);
+        assert_eq!(times.checkpoints.capacity(), 10);
+        assert_eq!(times.prune(Duration::ZERO), Err(TimePruneError::Empty));
+
+        // This should behave as if it was freshly constructed.
+        let mut fresh = OffsetTimeList::default();
+        assert_eq!(times.add(now), 0);
+        assert_eq!(fresh.add(now), 0);
+        assert_eq!(times.add(now), fresh.add(now));
+        assert_eq!(times.time_offsets, fresh.time_offsets);
+        assert_eq!(times.first(), Some(now));
+        assert_eq!(
+            times.prune(Duration::ZERO),
+            Err(TimePruneError::NoCheckpoints)
+        );
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let mut times = OffsetTimeList::default();
+        assert_eq!(times.capacity(), (0, 0));
+
+        let now = Instant::now();
+        for i in 0..100 {
+            times.add(now + Duration::from_secs(i));
+            if i % 10 == 0 {
+                times.checkpoint();
+            }
+        }
+        let (time_capacity, checkpoint_capacity) = times.capacity();
+        assert!(time_capacity >= 99);
+        assert!(checkpoint_capacity >= 10);
+
+        // Pruning keeps the old capacity around.
+        times.prune(Duration::from_secs(15)).unwrap();
+        assert_eq!(times.len(), 20);
+        assert_eq!(times.checkpoint_count(), 1);
+        assert_eq!(times.capacity(), (time_capacity, checkpoint_capacity));
+
+        times.shrink_to_fit();
+        assert_eq!(times.capacity(), (19, 1));
+        assert_eq!(times.len(), 20);
+        assert_eq!(times.first(), Some(now + Duration::from_secs(80)));
+    }
+
+    #[test]
+    fn test_prune_all() {
+        let mut times = OffsetTimeList::default();
+        assert_eq!(times.prune_all(), None);
+
+        // No checkpoints were ever added.
+        let now = Instant::now();
+        for i in 0..5 {
+            times.add(now + Duration::from_secs(i));
+        }
+        assert_eq!(times.prune_all(), Some(5));
+        assert!(times.time_offsets.is_empty());
+        assert_eq!(times.current_time, None);
+        assert_eq!(times.prune_all(), None);
+
+        times.add(now);
+        times.checkpoint();
+        times.add(now);
+        assert_eq!(times.prune_all(), Some(2));
+        assert!(times.checkpoints.is_empty());
+        assert_eq!(times.time_range(), None);
+    }
+
+    #[test]
+    fn test_reset_and_shrink() {
+        let mut times = OffsetTimeList::with_both_capacity(10, 10);
+
+        let now = Instant::now();
+        times.add(now);
+        times.add(now);
+        times.checkpoint();
+
+        times.reset_and_shrink();
+        assert!(times.time_offsets.is_empty());
+        assert!(times.checkpoints.is_empty());
+        assert_eq!(times.current_time, None);
+        assert_eq!(times.time_offsets.capacity(), 0);
+        assert_eq!(times.checkpoints.capacity(), 0);
+
+        assert_eq!(times.add(now), 0);
+    }
+
+    #[test]
+    fn test_time_range() {
+        let mut times = OffsetTimeList::default();
+        assert_eq!(times.time_range(), None);
+
+        let now = Instant::now();
+        times.add(now);
+        assert_eq!(times.time_range(), Some((now, now)));
+
+        let later = now + Duration::from_millis(1500);
+        times.add(now + Duration::from_millis(500));
+        times.add(later);
+        assert_eq!(times.time_range(), Some((now, later)));
+    }
+
+    /// Gaps too large for a `u32` of milliseconds shouldn't be truncated.
+    #[test]
+    fn test_wide_offsets() {
+        let mut times = OffsetTimeList::default();
+
+        // About 50 days, and a gap that is exactly the marker value.
+        let wide = u64::from(u32::MAX) + 1000;
+        let now = Instant::now();
+        let expected = [
+            0,
+            100,
+            100 + wide,
+            200 + wide,
+            200 + wide + u64::from(u32::MAX),
+        ]
+        .map(|ms| now + Duration::from_millis(ms));
+        for (index, &time) in expected.iter().enumerate() {
+            assert_eq!(times.add(time), index);
+            if index == 2 {
+                times.checkpoint();
+            }
+        }
+
+        assert_eq!(times.wide_offsets, vec![wide, u64::from(u32::MAX)]);
+        assert_eq!(
+            times.total_span(),
+            Some(expected[4].duration_since(expected[0]))
+        );
+        for (index, &time) in expected.iter().enumerate() {
+            assert_eq!(times.get(index), Some(time));
+        }
+        assert!(times.iter().map(|(_, time)| time).eq(expected));
+        assert!(
+            times
+                .iter()
+                .rev()
+                .map(|(_, time)| time)
+                .eq(expected.into_iter().rev())
+        );
+        assert_eq!(
+            times.iter_offsets().collect::<Vec<_>>(),
+            [100, wide, 100, u64::from(u32::MAX)].map(Duration::from_millis)
+        );
+        assert_eq!(
+            times
+                .iter_with_offsets()
+                .rev()
+                .map(|(offset, _)| offset)
+                .collect::<Vec<_>>(),
+            vec![u64::from(u32::MAX), 100, wide, 100, 0]
+        );
+
+        // Round-tripping through the parts and UNIX timestamps.
+        let (current_time, offsets) = times.clone().into_parts();
+        assert_eq!(
+            OffsetTimeList::from_parts(current_time, offsets),
+            Ok(times.clone())
+        );
+        // The latest time is in the future, so that the oldest is around now.
+        let span = times.total_span().unwrap();
+        let millis = times.to_unix_millis(SystemTime::now() + span);
+        let from_millis = OffsetTimeList::from_unix_millis(&millis).unwrap();
+        assert_eq!(from_millis.as_offsets(), times.as_offsets());
+        assert_eq!(from_millis.wide_offsets, times.wide_offsets);
+
+        // Pruning past the first wide offset drops it from the side table too.
+        assert_eq!(
+            times.prune(Duration::from_secs(60 * 60 * 24)),
+            Ok(PruneResult {
+                removed: 2,
+                remaining: 3
+            })
+        );
+        assert_eq!(times.wide_offsets, vec![u64::from(u32::MAX)]);
+        assert_eq!(
+            times.total_span(),
+            Some(expected[4].duration_since(expected[2]))
+        );
+        assert!(
+            times
+                .iter()
+                .map(|(_, time)| time)
+                .eq(expected[2..].iter().copied())
+        );
+    }
+
+    #[test]
+    fn test_total_span() {
+        let mut times = OffsetTimeList::default();
+        assert_eq!(times.total_span(), None);
+
+        let now = Instant::now();
+        times.add(now);
+        assert_eq!(times.total_span(), Some(Duration::ZERO));
+
+        for ms in [100, 300, 350, 1000, 1500] {
+            times.add(now + Duration::from_millis(ms));
+            if ms == 350 {
+                times.checkpoint();
+            }
+        }
+        assert_eq!(times.total_span(), Some(Duration::from_millis(1500)));
+
+        // Pruning drops everything before the checkpoint at 350ms.
+        assert_eq!(
+            times.prune(Duration::from_millis(1000)),
+            Ok(PruneResult {
+                removed: 3,
+                remaining: 3
+            })
+        );
+        assert_eq!(times.first(), Some(now + Duration::from_millis(350)));
+        assert_eq!(times.total_span(), Some(Duration::from_millis(1150)));
+
+        times.add(now + Duration::from_millis(2000));
+        assert_eq!(times.total_span(), Some(Duration::from_millis(1650)));
+        let (first, last) = times.time_range().unwrap();
+        assert_eq!(times.total_span(), Some(last - first));
+
+        times.reset();
+        assert_eq!(times.total_span(), None);
+        times.add(now);
+        assert_eq!(times.total_span(), Some(Duration::ZERO));
+
+        let start = unix_millis_now();
+        let times = OffsetTimeList::from_unix_millis(&[start, start + 250, start + 3000]).unwrap();
+        assert_eq!(times.total_span(), Some(Duration::from_millis(3000)));
+    }
+
+    #[test]
+    fn test_average_interval() {
+        let mut times = OffsetTimeList::default();
+        assert_eq!(times.average_interval(), None);
+        assert_eq!(times.interval_std_dev(), None);
+
+        let now = Instant::now();
+        times.add(now);
+        assert_eq!(times.average_interval(), None);
+        assert_eq!(times.interval_std_dev(), None);
+
+        times.add(now + Duration::from_millis(1000));
+        assert_eq!(times.average_interval(), Some(Duration::from_millis(1000)));
+        assert_eq!(times.interval_std_dev(), Some(Duration::ZERO));
+
+        // Intervals of 1000, 3000, 1000, 3000.
+        times.add(now + Duration::from_millis(4000));
+        times.add(now + Duration::from_millis(5000));
+        times.add(now + Duration::from_millis(8000));
+        assert_eq!(times.average_interval(), Some(Duration::from_millis(2000)));
+        assert_eq!(times.interval_std_dev(), Some(Duration::from_millis(1000)));
+    }
+
+    #[test]
+    fn test_iter_with_index() {
+        let mut times = OffsetTimeList::default();
+        assert_eq!(times.iter_with_index().next(), None);
+
+        let now = Instant::now();
+        let expected = [
+            now,
+            now + Duration::from_millis(100),
+            now + Duration::from_millis(250),
+            now + Duration::from_millis(1000),
+        ];
+
+        for time in expected {
+            times.add(time);
+        }
+
+        let iter = times.iter_with_index();
+        assert_eq!(iter.len(), expected.len());
+        assert_eq!(
+            iter.collect::<Vec<_>>(),
+            expected.into_iter().enumerate().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            times.iter_with_index().rev().collect::<Vec<_>>(),
+            expected.into_iter().enumerate().rev().collect::<Vec<_>>()
+        );
+
+        // Mixing both ends should meet in the middle.
+        let mut iter = times.iter_with_index();
+        assert_eq!(iter.next(), Some((0, expected[0])));
+        assert_eq!(iter.next_back(), Some((3, expected[3])));
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.next_back(), Some((2, expected[2])));
+        assert_eq!(iter.next(), Some((1, expected[1])));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn test_iter_matches_add() {
+        let mut times = OffsetTimeList::default();
+        assert_eq!(times.iter().len(), 0);
+
+        let now = Instant::now();
+        let expected = [0, 10, 10, 250, 1000, 60_000]
+            .map(|ms| now + Duration::from_millis(ms))
+            .to_vec();
+
+        let indices = expected
+            .iter()
+            .map(|&time| times.add(time))
+            .collect::<Vec<_>>();
+        assert_eq!(indices, (0..expected.len()).collect::<Vec<_>>());
+
+        let iter = times.iter();
+        assert_eq!(iter.len(), expected.len());
+        assert_eq!(
+            iter.collect::<Vec<_>>(),
+            indices.into_iter().zip(expected).collect::<Vec<_>>()
+        );
+        assert!(times.iter().eq(times.iter_with_index()));
+    }
+
+    #[test]
+    fn test_get() {
+        let mut times = OffsetTimeList::default();
+        assert_eq!(times.get(0), None);
+
+        let now = Instant::now();
+        let expected = [0, 10, 10, 250, 1000, 60_000]
+            .map(|ms| now + Duration::from_millis(ms))
+            .to_vec();
+
+        let indices = expected
+            .iter()
+            .map(|&time| times.add(time))
+            .collect::<Vec<_>>();
+
+        let last = indices.len() - 1;
+        assert_eq!(times.get(indices[0]), Some(expected[0]));
+        assert_eq!(times.get(indices[3]), Some(expected[3]));
+        assert_eq!(times.get(indices[last]), Some(expected[last]));
+        assert_eq!(times.get(times.len()), None);
+
+        for (index, time) in times.iter_with_index() {
+            assert_eq!(times.get(index), Some(time));
+        }
+    }
+
+    #[test]
+    fn test_first_last() {
+        let mut times = OffsetTimeList::default();
+        assert_eq!(times.first(), None);
+        assert_eq!(times.last(), None);
+
+        let now = Instant::now();
+        let expected = (0..10)
+            .map(|i| now + Duration::from_millis(i * 100))
+            .collect::<Vec<_>>();
+        for (index, &time) in expected.iter().enumerate() {
+            times.add(time);
+            if index % 3 == 0 {
+                times.checkpoint();
+            }
+        }
+        assert_eq!(times.first(), Some(expected[0]));
+        assert_eq!(times.last(), Some(expected[9]));
+
+        // Checkpoints are at 0, 3, 6, and 9; the one at 3 is the youngest at least 600ms old.
+        let result = times.prune(Duration::from_millis(600)).unwrap();
+        assert_eq!(
+            result,
+            PruneResult {
+                removed: 3,
+                remaining: 7
+            }
+        );
+        assert_eq!(times.first(), Some(expected[3]));
+        assert_eq!(times.last(), Some(expected[9]));
+        assert_eq!(times.time_range(), Some((expected[3], expected[9])));
+
+        times.prune_all();
+        assert_eq!(times.first(), None);
+        assert_eq!(times.last(), None);
+    }
+
+    #[test]
+    fn test_iter_with_offsets() {
+        let mut times = OffsetTimeList::default();
+        assert_eq!(times.iter_with_offsets().next(), None);
+
+        let now = Instant::now();
+        let millis = [0, 10, 10, 250, 1000];
+        for ms in millis {
+            times.add(now + Duration::from_millis(ms));
+        }
+
+        let iter = times.iter_with_offsets();
+        assert_eq!(iter.len(), millis.len());
+        assert_eq!(
+            iter.collect::<Vec<_>>(),
+            vec![
+                (0, now),
+                (10, now + Duration::from_millis(10)),
+                (0, now + Duration::from_millis(10)),
+                (240, now + Duration::from_millis(250)),
+                (750, now + Duration::from_millis(1000)),
+            ]
+        );
+        assert_eq!(
+            times.iter_with_offsets().next_back(),
+            Some((750, now + Duration::from_millis(1000)))
+        );
+    }
+
+    #[test]
+    fn test_offsets() {
+        let mut times = OffsetTimeList::default();
+        assert!(times.as_offsets().is_empty());
+
+        let now = Instant::now();
+        times.add(now);
+        assert!(times.as_offsets().is_empty());
+
+        let millis = [100, 150, 1150, 1150, 4000];
+        for ms in millis {
+            times.add(now + Duration::from_millis(ms));
+        }
+
+        assert_eq!(times.as_offsets(), &[100, 50, 1000, 0, 2850]);
+        assert_eq!(times.as_offsets().len(), times.len() - 1);
+        assert_eq!(times.iter_offsets().len(), 5);
+        assert_eq!(
+            times.iter_offsets().next(),
+            Some(Duration::from_millis(100))
+        );
+
+        let (first, last) = times.time_range().unwrap();
+        assert_eq!(times.iter_offsets().sum::<Duration>(), last - first);
+        assert_eq!(last - first, Duration::from_millis(4000));
+    }
+
+    #[test]
+    fn test_unix_millis() {
+        let times = OffsetTimeList::default();
+        assert!(times.to_unix_millis(SystemTime::now()).is_empty());
+
+        let empty = OffsetTimeList::from_unix_millis(&[]).unwrap();
+        assert_eq!(empty.time_range(), None);
+
+        let start = unix_millis_now();
+        let millis = [0, 500, 500, 3_000, 9_250].map(|ms| start + ms);
+        let times = OffsetTimeList::from_unix_millis(&millis).unwrap();
+        assert_eq!(times.time_offsets, vec![500, 0, 2_500, 6_250]);
+        assert!(times.checkpoints.is_empty());
+
+        let epoch = UNIX_EPOCH + Duration::from_millis(start + 9_250);
+        assert_eq!(times.to_unix_millis(epoch), millis);
+
+        // Sub-millisecond precision is dropped.
+        assert_eq!(
+            times.to_unix_millis(epoch + Duration::from_micros(900)),
+            millis
+        );
+
+        // Times before the UNIX epoch are clamped.
+        assert_eq!(
+            times.to_unix_millis(UNIX_EPOCH + Duration::from_millis(2_000)),
+            vec![0, 0, 0, 0, 2_000]
+        );
+
+        assert_eq!(
+            OffsetTimeList::from_unix_millis(&[1, 2, 2, 1, 0]).unwrap_err(),
+            UnixMillisError::Unordered(TimeOrderError { position: 3 })
+        );
+
+        // Times that can't be represented as an `Instant` are an error, rather than
+        // being silently moved.
+        let now = Instant::now();
+        let second = Duration::from_secs(1);
+        assert_eq!(instant_since(now, Ok(second)), Some(now - second));
+        assert_eq!(instant_since(now, Err(second)), Some(now + second));
+        assert_eq!(instant_since(now, Ok(Duration::MAX)), None);
+        assert_eq!(instant_since(now, Err(Duration::MAX)), None);
+
+        // The oldest time is checked as well as the latest. Here the span is too large
+        // for a `u64` of nanoseconds, even where `Instant` can go back to the 1970s.
+        assert_eq!(
+            OffsetTimeList::from_unix_millis(&[0, start, u64::MAX]),
+            Err(UnixMillisError::OutOfRange)
+        );
+        assert_eq!(
+            UnixMillisError::OutOfRange.to_string(),
+            "the times cannot be represented as instants"
+        );
+    }
+
+    #[test]
+    fn test_parts() {
+        let mut times = OffsetTimeList::default();
+        let now = Instant::now();
+        for ms in [0, 100, 250, 1000] {
+            times.add(now + Duration::from_millis(ms));
+        }
+        times.checkpoint();
+
+        let (current_time, offsets) = times.clone().into_parts();
+        assert_eq!(current_time, Some(now + Duration::from_millis(1000)));
+        assert_eq!(offsets, vec![100, 150, 750]);
+
+        let rebuilt = OffsetTimeList::from_parts(current_time, offsets).unwrap();
+        assert_eq!(rebuilt, times);
+        assert_eq!(rebuilt.total_span(), times.total_span());
+        assert_eq!(rebuilt.checkpoint_count(), 0);
+
+        assert_eq!(
+            OffsetTimeList::from_parts(None, vec![]),
+            Ok(OffsetTimeList::default())
+        );
+        assert_eq!(
+            OffsetTimeList::from_parts(Some(now), vec![]).map(|times| times.len()),
+            Ok(1)
+        );
+    }
+
+    #[test]
+    fn test_parts_invalid() {
+        assert_eq!(
+            OffsetTimeList::from_parts(None, vec![100]),
+            Err(TimePartsError::MissingCurrentTime)
+        );
+
+        // These add up to over 2^64 nanoseconds.
+        let now = Instant::now();
+        assert_eq!(
+            OffsetTimeList::from_parts(Some(now), vec![u32::MAX.into(); 5_000]),
+            Err(TimePartsError::SpanOverflow)
+        );
+
+        // These add up to over 2^64 milliseconds.
+        assert_eq!(
+            OffsetTimeList::from_parts(Some(now), vec![u64::MAX, 1]),
+            Err(TimePartsError::SpanOverflow)
+        );
+
+        assert_eq!(
+            TimePartsError::MissingCurrentTime.to_string(),
+            "there are offsets but no current time"
+        );
+    }
+
+    #[test]
+    fn test_prune() {
+        let mut times = OffsetTimeList::default();
+
+        // Test fully empty.
+        assert_eq!(
+            times.prune(Duration::from_secs(0)),
+            Err(TimePruneError::Empty)
+        );
+
+        let now = Instant::now();
+        times.add(now);
+
+        // Test no checkpoint.
+        assert_eq!(
+            times.prune(Duration::from_secs(0)),
+            Err(TimePruneError::NoCheckpoints)
+        );
+
+        // Add a checkpoint that isn't old enough, then clear it.
+        times.add(now);
+        times.checkpoint();
+
+        assert_eq!(
+            times.prune(Duration::from_secs(1000)),
+            Err(TimePruneError::NothingToPrune)
+        );
+        assert_eq!(
+            times.prune(Duration::from_secs(0)),
+            Ok(PruneResult {
+                removed: 1,
+                remaining: 1
+            })
+        );
+        assert!(times.checkpoints.is_empty());
+        assert_eq!(
+            times.prune(Duration::from_secs(0)),
+            Err(TimePruneError::NoCheckpoints)
+        );
+
+        assert_eq!(
+            TimePruneError::NoCheckpoints.to_string(),
+            "there are no checkpoints"
+        );
+    }
+
+    /// A checkpoint that is exactly `max_age` old should be used for pruning.
+    #[test]
+    fn test_prune_exact_age() {
+        let mut times = OffsetTimeList::default();
+
+        let now = Instant::now();
+        for i in 0..4 {
+            times.add(now + Duration::from_secs(i));
+            times.checkpoint();
+        }
+
+        // The checkpoint at index 1 is exactly 2s old, so only the time before it goes.
+        let mut exact = times.clone();
+        assert_eq!(
+            exact.prune(Duration::from_secs(2)),
+            Ok(PruneResult {
+                removed: 1,
+                remaining: 3
+            })
+        );
+        assert_eq!(exact.time_offsets, vec![1000, 1000]);
+        assert_eq!(exact.checkpoints.len(), 2);
+
+        // Slightly older than that only reaches the checkpoint at index 0, which has
+        // nothing before it.
+        let mut older = times.clone();
+        assert_eq!(
+            older.prune(Duration::from_millis(2001)),
+            Err(TimePruneError::NothingToPrune)
+        );
+        assert_eq!(older.time_offsets, vec![1000, 1000, 1000]);
+
+        // Slightly younger still uses the checkpoint at index 1.
+        let mut younger = times.clone();
+        assert_eq!(
+            younger.prune(Duration::from_millis(1999)),
+            Ok(PruneResult {
+                removed: 1,
+                remaining: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_prune_to_count() {
+        let mut times = OffsetTimeList::default();
+        assert_eq!(times.prune_to_count(5), 0);
+
+        // A time every second, with checkpoints at 2s, 5s, and 8s.
+        let now = Instant::now();
+        let expected = (0..10)
+            .map(|i| now + Duration::from_secs(i))
+            .collect::<Vec<_>>();
+        for (index, &time) in expected.iter().enumerate() {
+            times.add(time);
+            if index % 3 == 2 {
+                times.checkpoint();
+            }
+        }
+
+        assert_eq!(times.prune_to_count(10), 0);
+        assert_eq!(times.prune_to_count(100), 0);
+        assert_eq!(times.len(), 10);
+
+        // The checkpoint at 2s goes along with the removed times.
+        assert_eq!(times.prune_to_count(7), 3);
+        assert_eq!(times.first(), Some(expected[3]));
+        assert_eq!(times.total_span(), Some(Duration::from_secs(6)));
+        assert_eq!(times.checkpoints, vec![(expected[5], 2), (expected[8], 5)]);
+
+        // A checkpoint on the oldest remaining time is kept.
+        assert_eq!(times.prune_to_count(5), 2);
+        assert_eq!(times.checkpoints, vec![(expected[5], 0), (expected[8], 3)]);
+        assert!(
+            times
+                .iter()
+                .map(|(_, time)| time)
+                .eq(expected[5..].iter().copied())
+        );
+
+        // The checkpoints should still work for pruning by time afterwards.
+        assert_eq!(
+            times.prune(Duration::from_secs(1)),
+            Ok(PruneResult {
+                removed: 3,
+                remaining: 2
+            })
+        );
+
+        assert_eq!(times.prune_to_count(0), 2);
+        assert!(times.is_empty());
+        assert_eq!(times.checkpoint_count(), 0);
+    }
+
+    #[test]
+    fn test_max_checkpoints() {
+        let mut times = OffsetTimeList::default();
+        times.set_max_checkpoints(16);
+
+        // Checkpoint every second, for far longer than the cap.
+        let now = Instant::now();
+        for i in 0..10_000 {
+            times.add(now + Duration::from_secs(i));
+            times.checkpoint();
+            assert!(times.checkpoint_count() <= 16);
+            assert_eq!(
+                times.checkpoints.last(),
+                Some(&(now + Duration::from_secs(i), i as usize))
+            );
+        }
+
+        // The checkpoints should still be spread out over the whole range, with no gap
+        // much larger than an even split.
+        let gaps = times
+            .checkpoints
+            .windows(2)
+            .map(|w| w[1].1 - w[0].1)
+            .collect::<Vec<_>>();
+        let max_gap = *gaps.iter().max().unwrap();
+        assert!(max_gap <= 2 * 10_000 / 15, "{gaps:?}");
+        assert_eq!(times.checkpoints[0].1, 0);
+
+        // Pruning by age still removes about the right amount, within the gap between
+        // checkpoints, and never anything younger than the max age.
+        let mut pruned = times.clone();
+        let removed = pruned.prune(Duration::from_secs(5000)).unwrap().removed;
+        assert!(removed <= 5000);
+        assert!(removed >= 5000 - max_gap, "removed {removed}");
+        assert!(pruned.first().unwrap() <= now + Duration::from_secs(5000));
+
+        // Lowering the cap thins out the existing checkpoints straight away, and keeps
+        // the oldest and newest.
+        times.set_max_checkpoints(4);
+        assert_eq!(times.checkpoint_count(), 4);
+        assert_eq!(times.checkpoints[0].1, 0);
+        assert_eq!(times.checkpoints[3].1, 9_999);
+        assert!(times.prune(Duration::from_secs(5000)).is_ok());
+
+        // A cap of 1 keeps only the newest.
+        times.set_max_checkpoints(1);
+        assert_eq!(
+            times.checkpoints,
+            vec![(now + Duration::from_secs(9_999), times.len() - 1)]
+        );
+    }
+
+    /// Checkpoints should be recorded again as normal once a prune makes room.
+    #[test]
+    fn test_max_checkpoints_after_prune() {
+        let mut times = OffsetTimeList::default();
+        times.set_max_checkpoints(4);
+
+        let now = Instant::now();
+        let at = |secs| now + Duration::from_secs(secs);
+        for secs in 0..100 {
+            times.add(at(secs));
+            times.checkpoint();
+        }
+        assert_eq!(times.checkpoint_count(), 4);
+
+        // Prune everything up to and including the newest checkpoint.
+        assert!(times.prune(Duration::ZERO).is_ok());
+        assert_eq!(times.checkpoint_count(), 0);
+
+        for secs in 100..103 {
+            times.add(at(secs));
+            times.checkpoint();
+        }
+        assert_eq!(
+            times.checkpoints,
+            vec![(at(100), 1), (at(101), 2), (at(102), 3)]
+        );
+    }
+
+    /// Checkpoint indices should still line up with the stored times after a prune.
+    #[test]
+    fn test_prune_twice() {
+        let mut times = OffsetTimeList::default();
+
+        // A time every second, with checkpoints at 2s, 5s, and 8s.
+        let now = Instant::now();
+        let expected = (0..10)
+            .map(|i| now + Duration::from_secs(i))
+            .collect::<Vec<_>>();
+        for (index, &time) in expected.iter().enumerate() {
+            times.add(time);
+            if index % 3 == 2 {
+                times.checkpoint();
+            }
+        }
+
+        // Only the checkpoint at 2s is at least 5s old.
+        assert_eq!(
+            times.prune(Duration::from_secs(5)),
+            Ok(PruneResult {
+                removed: 2,
+                remaining: 8
+            })
+        );
+        assert_eq!(times.checkpoints, vec![(expected[5], 3), (expected[8], 6)]);
+
+        // The checkpoint at 5s is now at index 3, so only the times before it go.
+        assert_eq!(
+            times.prune(Duration::from_secs(3)),
+            Ok(PruneResult {
+                removed: 3,
+                remaining: 5
+            })
+        );
+        assert_eq!(times.checkpoints, vec![(expected[8], 3)]);
+        assert_eq!(
+            times.iter().map(|(_, time)| time).collect::<Vec<_>>(),
+            expected[5..]
+        );
+
+        assert_eq!(
+            times.prune(Duration::ZERO),
+            Ok(PruneResult {
+                removed: 3,
+                remaining: 2
+            })
+        );
+        assert_eq!(times.first(), Some(expected[8]));
+    }
+
+    /// Checkpoints are in ascending order of time, so their ages are descending; make
+    /// sure the youngest checkpoint that is old enough is the one used.
+    #[test]
+    fn test_prune_many_checkpoints() {
+        let mut times = OffsetTimeList::default();
+
+        // A time every 5s, with a checkpoint every 10s. The checkpoints are 40s, 30s,
+        // 20s, 10s, and 0s old, and are at indices 0, 2, 4, 6, and 8 respectively.
+        let now = Instant::now();
+        let expected = (0..=8)
+            .map(|i| now + Duration::from_secs(i * 5))
+            .collect::<Vec<_>>();
+        for (index, &time) in expected.iter().enumerate() {
+            times.add(time);
+            if index % 2 == 0 {
+                times.checkpoint();
+            }
+        }
+
+        // Which time is the oldest to survive for each `max_age`, in seconds.
+        for (max_age, first_kept) in [
+            (45, 0),
+            (40, 0),
+            (35, 0),
+            (30, 2),
+            (25, 2),
+            (20, 4),
+            (15, 4),
+            (10, 6),
+            (5, 6),
+            (0, 8),
+        ] {
+            let mut pruned = times.clone();
+            let result = pruned.prune(Duration::from_secs(max_age));
+
+            let expected_result = if first_kept > 0 {
+                Ok(PruneResult {
+                    removed: first_kept,
+                    remaining: expected.len() - first_kept,
+                })
+            } else {
+                Err(TimePruneError::NothingToPrune)
+            };
+            assert_eq!(result, expected_result, "pruning with max age {max_age}s");
+            assert_eq!(
+                pruned.iter().map(|(_, time)| time).collect::<Vec<_>>(),
+                expected[first_kept..],
+                "pruning with max age {max_age}s"
+            );
+        }
     }
 }
@@ -0,0 +1,232 @@
+//! A builder for an [`OffsetTimeList`] with a checkpoint policy.
+
+use std::time::Duration;
+
+use super::OffsetTimeList;
+
+/// A builder for an [`OffsetTimeList`] that automatically adds checkpoints in
+/// [`OffsetTimeList::add`], so that [`OffsetTimeList::prune`] can reclaim memory even
+/// if [`OffsetTimeList::checkpoint`] is never called.
+///
+/// If both [`OffsetTimeListBuilder::max_entries`] and
+/// [`OffsetTimeListBuilder::checkpoint_interval`] are set, a checkpoint is added when
+/// either of them calls for one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OffsetTimeListBuilder {
+    time_capacity: usize,
+    checkpoint_capacity: usize,
+    max_entries: Option<usize>,
+    checkpoint_interval: Option<Duration>,
+    max_checkpoints: Option<usize>,
+}
+
+impl OffsetTimeListBuilder {
+    /// Create a new [`OffsetTimeListBuilder`] with no checkpoint policy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the initial capacity for stored times.
+    pub fn time_capacity(mut self, capacity: usize) -> Self {
+        self.time_capacity = capacity;
+        self
+    }
+
+    /// Set the initial capacity for checkpoints.
+    pub fn checkpoint_capacity(mut self, capacity: usize) -> Self {
+        self.checkpoint_capacity = capacity;
+        self
+    }
+
+    /// Automatically add a checkpoint when adding a time if more than `max_entries`
+    /// times were added since the latest checkpoint, or since the start if there are
+    /// no checkpoints.
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    /// Automatically add a checkpoint when adding a time if the latest checkpoint is
+    /// at least `interval` older than it, or the oldest stored time if there are no
+    /// checkpoints.
+    ///
+    /// Calling [`OffsetTimeList::checkpoint`] manually still works, and the interval
+    /// is measured from it as well.
+    pub fn checkpoint_interval(mut self, interval: Duration) -> Self {
+        self.checkpoint_interval = Some(interval);
+        self
+    }
+
+    /// Cap the number of stored checkpoints. See [`OffsetTimeList::set_max_checkpoints`].
+    pub fn max_checkpoints(mut self, max_checkpoints: usize) -> Self {
+        self.max_checkpoints = Some(max_checkpoints);
+        self
+    }
+
+    /// Build the [`OffsetTimeList`].
+    pub fn build(self) -> OffsetTimeList {
+        let mut times =
+            OffsetTimeList::with_both_capacity(self.time_capacity, self.checkpoint_capacity);
+        times.max_entries = self.max_entries;
+        times.checkpoint_interval = self.checkpoint_interval;
+        if let Some(max_checkpoints) = self.max_checkpoints {
+            times.set_max_checkpoints(max_checkpoints);
+        }
+
+        times
+    }
+}
+
+impl OffsetTimeList {
+    /// Return an [`OffsetTimeListBuilder`] to build an [`OffsetTimeList`] with a
+    /// checkpoint policy.
+    pub fn builder() -> OffsetTimeListBuilder {
+        OffsetTimeListBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    #[test]
+    fn max_entries() {
+        let mut times = OffsetTimeList::builder().max_entries(3).build();
+        let now = Instant::now();
+
+        for i in 0..4 {
+            times.add(now + Duration::from_secs(i));
+        }
+        assert_eq!(times.checkpoint_count(), 0);
+
+        // Exceeding the limit adds a checkpoint, and the count starts over.
+        times.add(now + Duration::from_secs(4));
+        assert_eq!(times.checkpoints, vec![(now + Duration::from_secs(4), 4)]);
+
+        for i in 5..8 {
+            times.add(now + Duration::from_secs(i));
+        }
+        assert_eq!(times.checkpoint_count(), 1);
+        times.add(now + Duration::from_secs(8));
+        assert_eq!(times.checkpoint_count(), 2);
+
+        // Automatic checkpoints are capped like manual ones, keeping the newest.
+        let mut capped = OffsetTimeList::builder()
+            .max_entries(3)
+            .max_checkpoints(1)
+            .build();
+        for i in 0..=8 {
+            capped.add(now + Duration::from_secs(i));
+        }
+        assert_eq!(capped.checkpoints, vec![(now + Duration::from_secs(8), 8)]);
+
+        // A prune can now reclaim memory, even though `checkpoint` was never called.
+        let result = times.prune(Duration::from_secs(4)).unwrap();
+        assert_eq!(result.removed, 4);
+        assert_eq!(result.remaining, 5);
+    }
+
+    #[test]
+    fn max_entries_and_max_checkpoints() {
+        let mut times = OffsetTimeList::builder()
+            .max_entries(9)
+            .max_checkpoints(8)
+            .build();
+        let now = Instant::now();
+
+        // A checkpoint is due every 10 times, so 1000 times would need 100 of them.
+        for i in 0..1000 {
+            times.add(now + Duration::from_secs(i));
+            if i % 10 == 0 && i > 0 {
+                assert_eq!(times.checkpoints.last().map(|c| c.1), Some(i as usize));
+            }
+        }
+        assert_eq!(times.checkpoint_count(), 8);
+
+        // The capped checkpoints still cover the whole range from the first to the
+        // latest, with no gap much larger than an even split.
+        assert_eq!(times.checkpoints.first().map(|c| c.1), Some(10));
+        assert_eq!(times.checkpoints.last().map(|c| c.1), Some(990));
+        let gaps = times
+            .checkpoints
+            .windows(2)
+            .map(|w| w[1].1 - w[0].1)
+            .collect::<Vec<_>>();
+        assert!(
+            gaps.iter().all(|&gap| gap % 10 == 0 && gap <= 2 * 980 / 7),
+            "{gaps:?}"
+        );
+
+        // After pruning, automatic checkpoints are kept as normal again.
+        let removed = times.prune(Duration::from_secs(500)).unwrap().removed;
+        assert!(removed <= 500);
+        let count = times.checkpoint_count();
+        assert!(count < 8);
+        for i in 1000..1010 {
+            times.add(now + Duration::from_secs(i));
+        }
+        assert_eq!(times.checkpoint_count(), count + 1);
+        assert_eq!(times.checkpoints.last().map(|c| c.1), Some(1000 - removed));
+    }
+
+    #[test]
+    fn checkpoint_interval() {
+        let mut times = OffsetTimeList::with_checkpoint_interval(Duration::from_secs(5));
+        let now = Instant::now();
+        let at = |secs| now + Duration::from_secs(secs);
+
+        // The first checkpoint is 5s after the first time.
+        for secs in 0..5 {
+            times.add(at(secs));
+        }
+        assert_eq!(times.checkpoint_count(), 0);
+
+        for secs in 5..=12 {
+            times.add(at(secs));
+        }
+        assert_eq!(times.checkpoints, vec![(at(5), 5), (at(10), 10)]);
+
+        // A manual checkpoint restarts the interval.
+        times.add(at(13));
+        times.checkpoint();
+        for secs in 14..=18 {
+            times.add(at(secs));
+        }
+        assert_eq!(times.checkpoints.last(), Some(&(at(18), 18)));
+        assert_eq!(times.checkpoint_count(), 4);
+
+        // The interval is measured from the latest checkpoint, not a fixed grid, so
+        // the gap in times means the next checkpoint is at the first time after it.
+        times.add(at(20));
+        times.add(at(24));
+        assert_eq!(times.checkpoints.last(), Some(&(at(24), 20)));
+        assert_eq!(times.checkpoint_count(), 5);
+
+        assert_eq!(
+            times.prune(Duration::from_secs(10)),
+            Ok(crate::time::PruneResult {
+                removed: 13,
+                remaining: 8
+            })
+        );
+    }
+
+    #[test]
+    fn no_policy() {
+        let mut times = OffsetTimeList::builder()
+            .time_capacity(10)
+            .checkpoint_capacity(5)
+            .build();
+        assert_eq!(times, OffsetTimeList::default());
+        assert!(times.time_offsets.capacity() >= 10);
+        assert!(times.checkpoints.capacity() >= 5);
+
+        let now = Instant::now();
+        for i in 0..100 {
+            times.add(now + Duration::from_secs(i));
+        }
+        assert_eq!(times.checkpoint_count(), 0);
+    }
+}